@@ -16,7 +16,7 @@ use crate::module_bindings::pickup_item_reducer::pickup_item;
 use crate::module_bindings::drop_item_reducer::drop_item;
 use crate::module_bindings::combat_melee_reducer::combat_melee;
 use crate::module_bindings::combat_aoe_reducer::combat_aoe;
-use spacetimedb_sdk::{Identity, Table, TableWithPrimaryKey, DbContext};
+use spacetimedb_sdk::{Identity, Table, TableWithPrimaryKey, DbContext, Timestamp};
 use crate::module_bindings::{Player, GameItem};
 use std::time::Duration;
 use std::collections::HashMap;
@@ -101,7 +101,7 @@ impl GameState {
     pub fn find_nearby_items(&self, player_pos: (f32, f32), radius: f32) -> Vec<&GameItem> {
         self.items.values()
             .filter(|item| {
-                if let (Some(x), Some(y)) = (item.position_x, item.position_y) {
+                if let (Some(x), Some(y)) = (item.position_x, item.position_z) {
                     let dx = x - player_pos.0;
                     let dy = y - player_pos.1;
                     (dx * dx + dy * dy).sqrt() <= radius
@@ -135,11 +135,11 @@ impl GameState {
 
 /// Trait defining game actions for testable client operations
 pub trait GameActions {
-    fn move_player(&self, new_x: f32, new_y: f32) -> Result<(), GameError>;
+    fn move_player(&self, new_x: f32, new_z: f32) -> Result<(), GameError>;
     fn pickup_item(&self, item_id: u64) -> Result<(), GameError>;
     fn drop_item(&self, item_id: u64) -> Result<(), GameError>;
-    fn attack_player(&self, target: Identity, damage: u32) -> Result<(), GameError>;
-    fn aoe_attack(&self, center_x: f32, center_y: f32, radius: f32, damage: u32) -> Result<(), GameError>;
+    fn attack_player(&self, target_entity: u32, view_at: Option<Timestamp>) -> Result<(), GameError>;
+    fn aoe_attack(&self, center_x: f32, center_y: f32, center_z: f32, view_at: Option<Timestamp>) -> Result<(), GameError>;
     fn get_state(&self) -> Arc<Mutex<GameState>>;
     fn with_retry<F>(&self, f: F, max_retries: usize) -> Result<(), GameError>
         where F: FnMut() -> Result<(), GameError>;
@@ -147,8 +147,8 @@ pub trait GameActions {
 
 /// Real implementation of game actions against a DbConnection
 impl GameActions for DbConnection {
-    fn move_player(&self, new_x: f32, new_y: f32) -> Result<(), GameError> {
-        self.reducers.move_player(new_x, new_y)
+    fn move_player(&self, new_x: f32, new_z: f32) -> Result<(), GameError> {
+        self.reducers.move_player(new_x, new_z)
             .map_err(|e| GameError::SdkError(e.to_string()))
     }
     
@@ -162,13 +162,13 @@ impl GameActions for DbConnection {
             .map_err(|e| GameError::SdkError(e.to_string()))
     }
     
-    fn attack_player(&self, target: Identity, damage: u32) -> Result<(), GameError> {
-        self.reducers.combat_melee(target, damage)
+    fn attack_player(&self, target_entity: u32, view_at: Option<Timestamp>) -> Result<(), GameError> {
+        self.reducers.combat_melee(target_entity, view_at)
             .map_err(|e| GameError::SdkError(e.to_string()))
     }
     
-    fn aoe_attack(&self, center_x: f32, center_y: f32, radius: f32, damage: u32) -> Result<(), GameError> {
-        self.reducers.combat_aoe(center_x, center_y, radius, damage)
+    fn aoe_attack(&self, center_x: f32, center_y: f32, center_z: f32, view_at: Option<Timestamp>) -> Result<(), GameError> {
+        self.reducers.combat_aoe(center_x, center_y, center_z, view_at)
             .map_err(|e| GameError::SdkError(e.to_string()))
     }
     
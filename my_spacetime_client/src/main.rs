@@ -13,7 +13,7 @@ use crate::game_features::{GameActions, ChunkSubscriptionManager, MoveAndPickupC
 use crate::module_bindings::DbConnection;
 use std::io::Write;
 use phf::phf_map;
-use crate::module_bindings::spawn_rigid_body_reducer::spawn_rigid_body;
+use crate::module_bindings::spawn_rigid_body_str_reducer::spawn_rigid_body_str;
 use crate::module_bindings::despawn_rigid_body_reducer::despawn_rigid_body;
 use crate::module_bindings::physics_body_table::PhysicsBodyTableAccess;
 use crate::module_bindings::player_table::PlayerTableAccess;
@@ -73,7 +73,7 @@ impl GameContext {
     fn refresh_position(&mut self) {
         let conn = self.chunk_mgr.get_connection();
         if let Some(body) = conn.db.physics_body().iter().find(|p| p.entity_id == self.player_phy_entity_id) {
-            self.current_position = (body.pos_x, body.pos_y);
+            self.current_position = (body.pos_x, body.pos_z);
             println!("Current position updated to ({}, {})", self.current_position.0, self.current_position.1);
         }
         else {
@@ -84,12 +84,12 @@ impl GameContext {
 
 // Handler implementations
 fn cmd_move(ctx: &mut GameContext, parts: &[&str]) {
-    parse_args!(parts, "m <x> <y>", x: f32, y: f32);
+    parse_args!(parts, "m <x> <z>", x: f32, z: f32);
     // Execute move with a short-lived connection borrow
     // Perform move and drop the borrow before modifying `ctx`
     let move_result = {
         let conn_ref = ctx.chunk_mgr.get_connection();
-        with_retry(conn_ref, || conn_ref.move_player(x, y))
+        with_retry(conn_ref, || conn_ref.move_player(x, z))
     };
     if let Err(e) = move_result {
         println!("Failed to move: {}", e);
@@ -132,14 +132,14 @@ fn cmd_movepickup(ctx: &mut GameContext, parts: &[&str]) {
 }
 
 fn cmd_attack(ctx: &mut GameContext, parts: &[&str]) {
-    parse_args!(parts, "a <player_id> <damage>", pid_str: String, dmg: u32);
-    let target = spacetimedb_sdk::Identity::from_hex(pid_str.trim_start_matches("0x")).unwrap_or_default();
-    with_feedback(ctx, "Attack successful.", "Attack failed", |c| c.attack_player(target, dmg));
+    // damage and reach are defined by the server; no rewind timestamp from this client
+    parse_args!(parts, "a <entity_id>", target: u32);
+    with_feedback(ctx, "Attack successful.", "Attack failed", |c| c.attack_player(target, None));
 }
 
 fn cmd_aoe(ctx: &mut GameContext, parts: &[&str]) {
-    parse_args!(parts, "aoe <x> <y> <radius> <damage>", x: f32, y: f32, r: f32, dmg: u32);
-    with_feedback(ctx, "AOE attack successful.", "AOE attack failed", |c| c.aoe_attack(x, y, r, dmg));
+    parse_args!(parts, "aoe <x> <y> <z>", x: f32, y: f32, z: f32);
+    with_feedback(ctx, "AOE attack successful.", "AOE attack failed", |c| c.aoe_attack(x, y, z, None));
 }
 
 fn cmd_inventory(ctx: &mut GameContext, _parts: &[&str]) {
@@ -170,9 +170,9 @@ fn cmd_nearby(ctx: &mut GameContext, _parts: &[&str]) {
         println!("No items nearby.");
     } else {
         for i in nearby_items {
-            // i.position_x and position_y are guaranteed Some by find_nearby_items
+            // i.position_x and position_z are guaranteed Some by find_nearby_items
             let x = i.position_x.unwrap();
-            let y = i.position_y.unwrap();
+            let y = i.position_z.unwrap();
             let dist = ((x - player_pos.0).powi(2) + (y - player_pos.1).powi(2)).sqrt();
             println!("  [{}] {} at ({:.1}, {:.1}) - {:.1} units away", i.item_id, i.name, x, y, dist);
         }
@@ -199,20 +199,20 @@ fn cmd_fire_projectile(ctx: &mut GameContext, parts: &[&str]) {
     } else { 50.0 };
     
     // Convert angle to radians
-    //let angle_rad = angle * std::f32::consts::PI / 180.0;
+    let angle_rad = angle * std::f32::consts::PI / 180.0;
     
     // Current position
     let (x, y) = ctx.current_position;
     let z = 1.0; // Height above ground
     
     // Calculate velocity components
-    //let vel_x = speed * angle_rad.cos();
-    //let vel_z = speed * angle_rad.sin();
+    let vel_x = speed * angle_rad.cos();
+    let vel_z = speed * angle_rad.sin();
     
     let conn = ctx.chunk_mgr.get_connection();
     
     // Spawn a projectile (rigid body type 10)
-    match conn.reducers.spawn_rigid_body(
+    match conn.reducers.spawn_rigid_body_str(
         //ctx.player_id,  // entity_id (owner, but will be updated in user_data)
         0,              // region
         x,              // x position
@@ -220,6 +220,9 @@ fn cmd_fire_projectile(ctx: &mut GameContext, parts: &[&str]) {
         y,              // z position
         "Sphere(0.5)".to_string(), // small projectile
         10,             // PROJECTILE_BODY_TYPE
+        vel_x,          // initial velocity x
+        0.0,            // initial velocity y
+        vel_z,          // initial velocity z
     ) {
         Ok(_) => println!("Projectile fired at angle {} degrees, speed {}", angle, speed),
         Err(e) => println!("Failed to fire projectile: {}", e),
@@ -230,7 +233,7 @@ fn cmd_spawn_object(ctx: &mut GameContext, parts: &[&str]) {
     // Parse arguments: shape, body_type
     if parts.len() < 2 {
         println!("Usage: spawn <shape> <body_type>");
-        println!("  shape: Sphere(radius) or Box(x,y,z)");
+        println!("  shape: Sphere(r), Box(x,y,z), Capsule/Cylinder/Cone(half_height,r) or ConvexHull/TriMesh/HeightField(asset_id); append Sensor for a sensor");
         println!("  body_type: 0=static, 1=dynamic, 2=kinematic, 10=projectile, 20=player");
         return;
     }
@@ -246,13 +249,14 @@ fn cmd_spawn_object(ctx: &mut GameContext, parts: &[&str]) {
     // Spawn a rigid body with requested parameters in a scoped borrow
     let spawn_result = {
         let conn_ref = ctx.chunk_mgr.get_connection();
-        conn_ref.reducers.spawn_rigid_body(
+        conn_ref.reducers.spawn_rigid_body_str(
             0,              // region
             x,              // x position
             y,              // y position (height)
             z,              // z position
             shape.clone(),  // shape descriptor
             body_type,      // body type
+            0.0, 0.0, 0.0,  // initial velocity
         )
     };
     match spawn_result {
@@ -286,7 +290,7 @@ fn test_projectile_scenario(ctx: &mut GameContext) {
     
     // 1. Create a player target at a distance
     println!("1. Spawning player target at ({}, {})", x + 10.0, y);
-    match conn.reducers.spawn_rigid_body(
+    match conn.reducers.spawn_rigid_body_str(
         //Identity::from_hex("target00000000000000000000000000000000").unwrap_or_default(),
         0,              // region
         x + 10.0,       // 10 units in front
@@ -294,6 +298,7 @@ fn test_projectile_scenario(ctx: &mut GameContext) {
         y,              // y position
         "Sphere(1.0)".to_string(),
         20,             // PLAYER_BODY_TYPE
+        0.0, 0.0, 0.0,  // initial velocity
     ) {
         Ok(_) => println!("Target spawned successfully"),
         Err(e) => println!("Failed to spawn target: {}", e),
@@ -302,7 +307,7 @@ fn test_projectile_scenario(ctx: &mut GameContext) {
     // 2. Fire projectile at the target after a brief delay
     std::thread::sleep(std::time::Duration::from_millis(500));
     println!("2. Firing projectile at target");
-    match conn.reducers.spawn_rigid_body(
+    match conn.reducers.spawn_rigid_body_str(
         //ctx.player_id,  // entity_id (owner)
         0,              // region
         x,              // x position
@@ -310,6 +315,7 @@ fn test_projectile_scenario(ctx: &mut GameContext) {
         y,              // z position
         "Sphere(0.5)".to_string(),
         10,             // PROJECTILE_BODY_TYPE
+        20.0, 0.0, 0.0, // initial velocity towards the target
     ) {
         Ok(_) => println!("Projectile fired successfully"),
         Err(e) => println!("Failed to fire projectile: {}", e),
@@ -326,7 +332,7 @@ fn test_contact_duration_scenario(ctx: &mut GameContext) {
     
     // 1. Create a static body at current position
     println!("1. Spawning static object at ({}, {})", x, y);
-    let _ = conn.reducers.spawn_rigid_body(
+    let _ = conn.reducers.spawn_rigid_body_str(
         //ctx.player_id,
         0,
         x,
@@ -334,11 +340,12 @@ fn test_contact_duration_scenario(ctx: &mut GameContext) {
         y,
         "Sphere(2.0)".to_string(),
         0,         // STATIC_BODY_TYPE
+        0.0, 0.0, 0.0,  // initial velocity
     );
     
     // 2. Create a dynamic body just above it that will fall and make contact
     println!("2. Spawning dynamic object above it");
-    let _ = conn.reducers.spawn_rigid_body(
+    let _ = conn.reducers.spawn_rigid_body_str(
         //ctx.player_id,
         0,
         x,
@@ -346,6 +353,7 @@ fn test_contact_duration_scenario(ctx: &mut GameContext) {
         y,
         "Sphere(1.0)".to_string(),
         1,         // DYNAMIC_BODY_TYPE
+        0.0, 0.0, 0.0,  // initial velocity
     );
     
     println!("Test initiated. Objects should make contact and duration should be recorded.");
@@ -359,7 +367,7 @@ fn test_sensor_scenario(ctx: &mut GameContext) {
     
     // 1. Create a sensor zone at current position
     println!("1. Spawning sensor at ({}, {})", x, y);
-    match conn.reducers.spawn_rigid_body(
+    match conn.reducers.spawn_rigid_body_str(
         //Identity::from_hex("sensor00000000000000000000000000000000").unwrap_or_default(),
         0,              // region
         x,              // x position
@@ -367,6 +375,7 @@ fn test_sensor_scenario(ctx: &mut GameContext) {
         y,              // z position
         "Sphere(3.0)Sensor".to_string(),  // Add "Sensor" suffix to make it a sensor
         0,              // STATIC_BODY_TYPE
+        0.0, 0.0, 0.0,  // initial velocity
     ) {
         Ok(_) => println!("Sensor spawned successfully"),
         Err(e) => println!("Failed to spawn sensor: {}", e),
@@ -375,7 +384,7 @@ fn test_sensor_scenario(ctx: &mut GameContext) {
     // 2. Create a player body that will move through it
     std::thread::sleep(std::time::Duration::from_millis(500));
     println!("2. Spawning player body to enter sensor");
-    match conn.reducers.spawn_rigid_body(
+    match conn.reducers.spawn_rigid_body_str(
         //Identity::from_hex("dynamic0000000000000000000000000000000").unwrap_or_default(),
         0,              // region
         x - 10.0,       // Start outside sensor
@@ -383,6 +392,7 @@ fn test_sensor_scenario(ctx: &mut GameContext) {
         y,              // z position
         "Sphere(1.0)".to_string(),
         20,             // PLAYER_BODY_TYPE
+        0.0, 0.0, 0.0,  // initial velocity
     ) {
         Ok(_) => println!("Player body spawned successfully"),
         Err(e) => println!("Failed to spawn player body: {}", e),
@@ -439,9 +449,9 @@ fn cmd_show_physics_bodies(ctx: &mut GameContext, _parts: &[&str]) {
         println!("------------------|-------------------|--------------");
         for e in entities {
             let id = e.entity_id.to_hex();
-            let shape = e.collider_shape.clone();
+            let shape = e.colliders.first().map_or(String::new(), |c| format!("{:?}", c.shape));
             let body_type: u8 = e.body_type;
-            println!("{} | {:17} | ({:.1}, {:.1}) {}", id, shape, e.pos_x, e.pos_y, body_type);
+            println!("{} | {:17} | ({:.1}, {:.1}) {}", id, shape, e.pos_x, e.pos_z, body_type);
         }
     }
     println!();
@@ -519,7 +529,7 @@ fn main() {
     std::thread::sleep(std::time::Duration::from_millis(200));
     // Try loading position from server-side physics_body via chunk manager
     if let Some(body) = chunk_mgr.get_connection().db.physics_body().iter().find(|p| p.owner_id == player_id) {
-        current_position = (body.pos_x, body.pos_y);
+        current_position = (body.pos_x, body.pos_z);
     } else {
         println!("No player's physics_body found in subscribed chunks.");
     }
@@ -22,11 +22,24 @@ pub mod tables {
     pub mod player_buffs;
     pub mod damage_event;
     pub mod buff_expiry_schedule;
+    pub mod id_sequence;
+    pub mod region_config;
+    pub mod user_role;
+    pub mod physics_joint;
+    pub mod player_input;
+    pub mod projectile;
+    pub mod query_hit;
+    pub mod collider_asset;
+    pub mod physics_material;
+    pub mod physics_tick_stats;
+    pub mod despawn_event;
 }
 pub mod reducers {
     pub mod combat;
     pub mod lifecycle;
     pub mod world;
+    pub mod admin;
+    pub mod auth;
 }
 pub mod physics;
 
@@ -41,7 +54,7 @@ pub use tables::game_item::GameItem;
 pub use tables::contact_event::ContactEvent;
 // Re-export reducer functions
 pub use reducers::lifecycle::{module_init, on_client_connected, on_client_disconnected};
-pub use reducers::world::{move_player, pickup_item, drop_item};
+pub use reducers::world::{move_player, submit_input, pickup_item, drop_item};
 // Chunk subscription request reducer
 pub use reducers::combat::{_combat_melee, _combat_aoe};
 
@@ -0,0 +1,75 @@
+//! Shared collider geometry (convex hulls, triangle meshes, heightfields) referenced by ID,
+//! and loading of everything a body row references by ID or name
+use std::collections::HashMap;
+use spacetimedb::{reducer, ReducerContext, Table};
+use crate::reducers::auth::{require_admin, role_of};
+use crate::tables::collider_asset::{collider_asset, ColliderAsset, ColliderAssetData};
+use crate::tables::physics_body::{physics_body, PhysicsBody};
+use crate::tables::physics_material::{physics_material, PhysicsMaterial};
+use crate::tables::user_role::Role;
+
+/// Asset geometry and materials a physics_body row refers to, loaded before its Rapier body is built
+#[derive(Default)]
+pub struct BodyResources {
+    pub assets: HashMap<u64, ColliderAssetData>,
+    pub materials: HashMap<String, PhysicsMaterial>,
+    /// The row's owner is a Builder or above, so its specs' `contact_force_threshold`s apply
+    pub trusted_specs: bool,
+}
+
+impl BodyResources {
+    pub fn load(ctx: &ReducerContext, row: &PhysicsBody) -> Self {
+        let assets = row.colliders.iter()
+            .filter_map(|spec| spec.shape.asset_id())
+            .filter_map(|id| ctx.db.collider_asset().asset_id().find(id).map(|a| (id, a.data)))
+            .collect();
+        let materials = row.colliders.iter()
+            .filter_map(|spec| spec.material.as_ref())
+            .chain(row.material.as_ref())
+            .filter_map(|name| ctx.db.physics_material().name().find(name))
+            .map(|m| (m.name.clone(), m))
+            .collect();
+        let trusted_specs = role_of(ctx, row.owner_id) >= Role::Builder;
+        BodyResources { assets, materials, trusted_specs }
+    }
+
+    /// Resolve an optional material name; naming a missing material is an error
+    pub fn material(&self, name: Option<&String>) -> Result<Option<&PhysicsMaterial>, String> {
+        match name {
+            Some(name) => self.materials.get(name).map(Some).ok_or_else(|| format!("Unknown physics material '{}'", name)),
+            None => Ok(None),
+        }
+    }
+}
+
+#[reducer]
+/// Store mesh-type collider geometry once so bodies can reference it by ID (admin only)
+pub fn create_collider_asset(ctx: &ReducerContext, name: String, data: ColliderAssetData) -> Result<(), String> {
+    require_admin(ctx)?;
+    data.validate()?;
+    if ctx.db.collider_asset().name().find(&name).is_some() {
+        return Err(format!("Collider asset '{}' already exists", name));
+    }
+    let row = ctx.db.collider_asset().insert(ColliderAsset {
+        asset_id: 0,
+        name,
+        data,
+        created_by: ctx.sender,
+        created_at: ctx.timestamp,
+    });
+    log::info!("Collider asset {} '{}' created", row.asset_id, row.name);
+    Ok(())
+}
+
+#[reducer]
+/// Delete an asset no body references any more (admin only)
+pub fn delete_collider_asset(ctx: &ReducerContext, asset_id: u64) -> Result<(), String> {
+    require_admin(ctx)?;
+    if ctx.db.physics_body().iter().any(|b| b.colliders.iter().any(|c| c.shape.asset_id() == Some(asset_id))) {
+        return Err("Collider asset is still in use".into());
+    }
+    if !ctx.db.collider_asset().asset_id().delete(asset_id) {
+        return Err("Collider asset not found".into());
+    }
+    Ok(())
+}
@@ -0,0 +1,142 @@
+//! Kinematic character controller driving player bodies
+use rapier3d::prelude::*;
+use rapier3d::control::{CharacterAutostep, CharacterLength, KinematicCharacterController};
+use spacetimedb::{ReducerContext, Table};
+use crate::physics::{hooks, PhysicsContext};
+use crate::tables::player::player;
+use crate::tables::player_input::player_input;
+
+/// Steepest slope (radians) a character can walk up
+pub const MAX_SLOPE_CLIMB_ANGLE: Real = std::f32::consts::FRAC_PI_4;
+/// Slopes steeper than this (radians) make the character slide down
+pub const MIN_SLOPE_SLIDE_ANGLE: Real = std::f32::consts::FRAC_PI_6;
+/// Tallest step (world units) climbed automatically
+pub const MAX_STEP_HEIGHT: Real = 0.3;
+/// Distance (world units) within which a character is snapped down onto the ground
+pub const SNAP_TO_GROUND_DISTANCE: Real = 0.2;
+/// Server-side walking speed (units/s)
+pub const MAX_WALK_SPEED: Real = 5.0;
+/// Sprint speed multiplier
+pub const SPRINT_MULTIPLIER: Real = 1.6;
+/// Initial upward speed of a jump (units/s)
+pub const JUMP_SPEED: Real = 5.0;
+
+/// Per-character movement state kept alongside the Rapier world
+#[derive(Clone, Copy, Debug, Default)]
+pub struct CharacterState {
+    /// Translation requested by `move_player`, resolved against the world on the next tick
+    pub desired_translation: Option<Vector<Real>>,
+    /// Velocity along gravity accumulated while airborne
+    pub fall_velocity: Vector<Real>,
+    pub grounded: bool,
+    /// Grounded flag last written to physics_body
+    pub persisted_grounded: bool,
+}
+
+/// Build the controller for a world; "up" is opposite to gravity (+y when there is none)
+pub fn make_controller(gravity: &Vector<Real>) -> KinematicCharacterController {
+    let up = UnitVector::try_new(-gravity, 1.0e-6).unwrap_or(Vector::y_axis());
+    KinematicCharacterController {
+        up,
+        offset: CharacterLength::Absolute(0.01),
+        slide: true,
+        autostep: Some(CharacterAutostep {
+            max_height: CharacterLength::Absolute(MAX_STEP_HEIGHT),
+            min_width: CharacterLength::Absolute(0.2),
+            include_dynamic_bodies: false,
+        }),
+        max_slope_climb_angle: MAX_SLOPE_CLIMB_ANGLE,
+        min_slope_slide_angle: MIN_SLOPE_SLIDE_ANGLE,
+        snap_to_ground: Some(CharacterLength::Absolute(SNAP_TO_GROUND_DISTANCE)),
+        ..KinematicCharacterController::default()
+    }
+}
+
+/// The part of `v` in the movement plane (perpendicular to the controller's "up")
+pub fn planar(v: Vector<Real>, up: &UnitVector<Real>) -> Vector<Real> {
+    v - up.into_inner() * v.dot(up)
+}
+
+/// Resolve every character's desired translation plus gravity against the world and
+/// queue the result as the body's next kinematic position. `dt` is the simulated time of this tick.
+pub fn move_characters(world: &mut PhysicsContext, dt: Real) {
+    if dt <= 0.0 {
+        return;
+    }
+    for (entity_id, state) in world.characters.iter_mut() {
+        let Some(&handle) = world.id_to_body.get(entity_id) else { continue };
+        let Some(body) = world.bodies.get(handle) else { continue };
+        // compound bodies may carry sensors (auras); the controller moves the solid part
+        let Some(collider) = body.colliders().iter()
+            .filter_map(|&c| world.colliders.get(c))
+            .find(|c| !c.is_sensor()) else { continue };
+
+        // fall while airborne (a fresh jump counts as airborne); snap-to-ground keeps grounded characters down
+        if state.grounded {
+            state.fall_velocity = Vector::zeros();
+        } else {
+            state.fall_velocity += world.gravity * dt;
+        }
+        // never move faster than a sprint, whatever the request
+        let max_step = MAX_WALK_SPEED * SPRINT_MULTIPLIER * dt;
+        let walk = state.desired_translation.take().unwrap_or_else(Vector::zeros).cap_magnitude(max_step);
+        let desired = walk + state.fall_velocity * dt;
+
+        // the controller uses scene queries, not contacts, so apply the hook rules it needs here
+        let passable = |_, other: &Collider| !hooks::passes_through(collider, other);
+        let filter = QueryFilter::default().exclude_rigid_body(handle).exclude_sensors().predicate(&passable);
+        let movement = world.character_controller.move_shape(
+            dt,
+            &world.bodies,
+            &world.colliders,
+            &world.query_pipeline,
+            collider.shape(),
+            collider.position(),
+            desired,
+            filter,
+            |_| {},
+        );
+        state.grounded = movement.grounded;
+
+        let next = body.translation() + movement.translation;
+        if let Some(body) = world.bodies.get_mut(handle) {
+            body.set_next_kinematic_translation(next);
+        }
+    }
+}
+
+/// Translation for one tick of walking along `dir` (projected onto the movement plane and
+/// capped at full speed)
+pub fn input_translation(dir: Vector<Real>, sprint: bool, up: &UnitVector<Real>, dt: Real) -> Vector<Real> {
+    let speed = if sprint { MAX_WALK_SPEED * SPRINT_MULTIPLIER } else { MAX_WALK_SPEED };
+    planar(dir, up).cap_magnitude(1.0) * speed * dt
+}
+
+/// Turn the stored input intents of players with a character in this world into desired
+/// translations for the next `dt` seconds, and acknowledge their sequence numbers
+pub fn consume_inputs(ctx: &ReducerContext, world: &mut PhysicsContext, dt: Real) {
+    if dt <= 0.0 {
+        return;
+    }
+    let up = world.character_controller.up;
+    for mut input in ctx.db.player_input().iter() {
+        let Some(player) = ctx.db.player().player_id().find(input.player_id) else { continue };
+        let Some(state) = world.characters.get_mut(&player.phy_entity_id) else { continue };
+
+        // walk in the plane perpendicular to "up", at most at full speed
+        let dir = vector![input.dir_x, input.dir_y, input.dir_z];
+        state.desired_translation = Some(input_translation(dir, input.sprint, &up, dt));
+
+        let jumped = input.jump && state.grounded;
+        if jumped {
+            state.fall_velocity = up.into_inner() * JUMP_SPEED;
+            state.grounded = false;
+        }
+
+        if input.last_processed_seq != input.seq || input.jump {
+            input.last_processed_seq = input.seq;
+            input.jump = false;
+            ctx.db.player_input().player_id().update(input);
+        }
+    }
+}
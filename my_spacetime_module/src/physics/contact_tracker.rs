@@ -5,23 +5,26 @@ use crate::tables::contact_event::ContactEvent;
 use crate::tables::contact_event::contact_event;
 use crate::tables::player_buffs::player_buffs;
 use crate::tables::physics_body::physics_body;
-use crate::physics::skills::{apply_damage, apply_buff};
+use crate::physics::skills::{apply_damage_in, apply_buff};
+use crate::spacetime_common::sequence::{next_id, Sequence};
+use crate::spacetime_common::collision::{object_function, PROJECTILE_BODY_TYPE};
+use crate::physics::projectile::on_projectile_hit;
+use crate::tables::despawn_event::DespawnReason;
+use std::collections::hash_map::Entry;
 
 
 pub use crate::physics::PHYSICS_CONTEXTS;
 pub use crate::physics::PhysicsContext;
 
-static CONTACT_EVENT_ID_COUNTER: AtomicU64 = AtomicU64::new(1);
-
-/// Map each Rapier collider handle to the originating player Identity
+/// Map each (region, Rapier collider handle) to the originating player Identity
 /// Not used
 /// Current implementation uses the collider's user_data to store entity_id
-static OWNER_OF_COLLIDER: Lazy<Mutex<HashMap<ColliderHandle, Identity>>> =
+static OWNER_OF_COLLIDER: Lazy<Mutex<HashMap<(u32, ColliderHandle), Identity>>> =
     Lazy::new(|| Mutex::new(HashMap::new()));
 
-/// External call to tag a collider with some data
-pub fn register_owner(handle: ColliderHandle, option_id: Identity) {
-    OWNER_OF_COLLIDER.lock().unwrap().insert(handle, option_id);
+/// External call to tag a collider of a region's world with some data
+pub fn register_owner(region: u32, handle: ColliderHandle, option_id: Identity) {
+    OWNER_OF_COLLIDER.lock().unwrap().insert((region, handle), option_id);
 }
 
 /// Domain events extracted from raw Rapier collisions
@@ -39,10 +42,135 @@ struct ContactState {
     pub tick_count: u8,
     pub buff_id: Option<u64>, // remember applied aura buff row id
 }
+/// (region, source handle, target handle, source id, target id, object_function)
+type ContactKey = (u32, ColliderHandle, ColliderHandle, u32, u32, u8);
+
 /// Track active contacts per skill-instance to source-target for sustained detection
-static ACTIVE_CONTACTS: Lazy<Mutex<HashMap<(ColliderHandle, ColliderHandle, u32, u32, u8), ContactState>>> =
+static ACTIVE_CONTACTS: Lazy<Mutex<HashMap<ContactKey, ContactState>>> =
     Lazy::new(|| Mutex::new(HashMap::new()));
 
+/// Move a collider's owner to its handle in another region's world (body handoff)
+pub fn move_collider_owner(from: (u32, ColliderHandle), to: (u32, ColliderHandle)) {
+    let mut owners = OWNER_OF_COLLIDER.lock().unwrap();
+    if let Some(owner) = owners.remove(&from) {
+        owners.insert(to, owner);
+    }
+}
+
+/// `damage_event.skill_id` for impact damage (next to the combat IDs)
+pub const IMPACT_SKILL_ID: u8 = 252;
+/// Impulse (N·s) absorbed without damage in a single tick
+pub const IMPACT_DAMAGE_MIN_IMPULSE: Real = 10.0;
+/// Damage per N·s above the minimum, capped at `IMPACT_DAMAGE_MAX`
+pub const IMPACT_DAMAGE_PER_IMPULSE: Real = 1.0;
+pub const IMPACT_DAMAGE_MAX: u32 = 100;
+
+/// Damage dealt by an impact of `impulse` N·s within one tick
+pub fn impact_damage(impulse: Real) -> u32 {
+    if impulse <= IMPACT_DAMAGE_MIN_IMPULSE {
+        return 0;
+    }
+    (((impulse - IMPACT_DAMAGE_MIN_IMPULSE) * IMPACT_DAMAGE_PER_IMPULSE) as u32).min(IMPACT_DAMAGE_MAX)
+}
+
+/// Sum the contact-force events of a tick into one impulse per entity pair and turn heavy
+/// impacts (falls, crushes, heavy hits) into damage for the non-fixed entities involved.
+/// Only colliders whose threshold came from a Builder-or-higher spec or a material emit
+/// these events (see `insert_body`), so players can't spawn damage dealers.
+pub fn handle_force_events(ctx: &ReducerContext, world: &mut PhysicsContext, events: &[ContactForceEvent], dt: Real) {
+    let mut impulses: HashMap<(u32, u32), Real> = HashMap::new();
+    for ev in events {
+        let (Some(c1), Some(c2)) = (world.colliders.get(ev.collider1), world.colliders.get(ev.collider2)) else { continue };
+        let (a, b) = (unpack_id(c1.user_data), unpack_id(c2.user_data));
+        // one entry per pair regardless of which collider Rapier reported first
+        let key = if a <= b { (a, b) } else { (b, a) };
+        *impulses.entry(key).or_insert(0.0) += ev.total_force_magnitude * dt;
+    }
+
+    for ((a, b), impulse) in impulses {
+        let damage = impact_damage(impulse);
+        log::debug!("Impact between {} and {}: impulse {:.1} -> damage {}", a, b, impulse, damage);
+        if damage == 0 {
+            continue;
+        }
+        for entity in [a, b] {
+            let movable = world.id_to_body.get(&entity)
+                .and_then(|h| world.bodies.get(*h))
+                .is_some_and(|body| !body.is_fixed());
+            if movable {
+                apply_damage_in(ctx, world, IMPACT_SKILL_ID, entity, damage);
+            }
+        }
+    }
+}
+
+/// Drop all contact state involving an entity that is being despawned
+pub fn forget_entity_contacts(entity_id: u32) {
+    ACTIVE_CONTACTS.lock().unwrap()
+        .retain(|(_, _, _, sid, tid, _), _| *sid != entity_id && *tid != entity_id);
+}
+
+/// Stop tracking the owners of colliders removed from a region's world
+pub fn forget_collider_owners(region: u32, handles: &[ColliderHandle]) {
+    let mut owners = OWNER_OF_COLLIDER.lock().unwrap();
+    for handle in handles {
+        owners.remove(&(region, *handle));
+    }
+}
+
+/// Handle an End for every tracked contact of this world matching `pred` (removing aura buffs
+/// and contact_event rows) as if Rapier had reported it stopped, then stop tracking it
+fn end_contacts_where(ctx: &ReducerContext, world: &mut PhysicsContext, pred: impl Fn(u32, u32) -> bool) {
+    let ended: Vec<_> = ACTIVE_CONTACTS.lock().unwrap().keys()
+        .filter(|(region, _, _, sid, tid, _)| *region == world.region && pred(*sid, *tid))
+        .cloned()
+        .collect();
+    for &(_, source_handle, target_handle, unpacked_source_id, unpacked_target_id, object_function) in &ended {
+        handle_event(ctx, world, PhysicsContact::End { source_handle, target_handle, unpacked_source_id, unpacked_target_id, object_function });
+    }
+    let mut contacts = ACTIVE_CONTACTS.lock().unwrap();
+    for key in &ended {
+        contacts.remove(key);
+    }
+}
+
+/// End every open contact of an entity that is about to be despawned or leave the world
+pub(crate) fn end_entity_contacts(ctx: &ReducerContext, world: &mut PhysicsContext, entity_id: u32) {
+    end_contacts_where(ctx, world, |sid, tid| sid == entity_id || tid == entity_id);
+}
+
+/// End the contacts a handoff splits: one side leaves for another region (`targets` maps
+/// leaving entities to their region) and the other doesn't follow it there
+pub(crate) fn end_split_contacts(ctx: &ReducerContext, world: &mut PhysicsContext, targets: &HashMap<u32, u32>) {
+    end_contacts_where(ctx, world, |sid, tid| match (targets.get(&sid), targets.get(&tid)) {
+        (None, None) => false,
+        (a, b) => a != b,
+    });
+}
+
+/// Re-key the contacts between colliders that moved together from one region's world to
+/// another, so they carry on there instead of starting again; `moved` maps old to new handles
+pub fn move_contacts(from: u32, to: u32, moved: &HashMap<ColliderHandle, ColliderHandle>) {
+    let mut contacts = ACTIVE_CONTACTS.lock().unwrap();
+    let keys: Vec<ContactKey> = contacts.keys()
+        .filter(|(r, s, t, ..)| *r == from && moved.contains_key(s) && moved.contains_key(t))
+        .cloned()
+        .collect();
+    for key @ (_, s, t, sid, tid, func) in keys {
+        if let Some(state) = contacts.remove(&key) {
+            contacts.insert((to, moved[&s], moved[&t], sid, tid, func), state);
+        }
+    }
+}
+
+/// End every tracked contact of a world's entities and drop its collider owners,
+/// before the world is unloaded
+pub(crate) fn release_world_contacts(ctx: &ReducerContext, world: &mut PhysicsContext) {
+    end_contacts_where(ctx, world, |_, _| true);
+    let region = world.region;
+    OWNER_OF_COLLIDER.lock().unwrap().retain(|(r, _), _| *r != region);
+}
+
 /// Collect and normalize raw Rapier events into PhysicsContact instances
 #[allow(unused_variables)]
 pub fn collect_events(
@@ -128,18 +256,30 @@ pub fn process_contacts(
     let raw = collect_events(events, world, region);
     let mut result = Vec::new();
     let mut map = ACTIVE_CONTACTS.lock().unwrap();
+    // A Stopped whose collider is already gone can't become an End event; drop its contact directly
+    for ev in events {
+        if let CollisionEvent::Stopped(h1, h2, _) = ev
+            && (world.colliders.get(*h1).is_none() || world.colliders.get(*h2).is_none())
+        {
+            map.retain(|(r, s, t, _, _, _), _| *r != region || !((s == h1 && t == h2) || (s == h2 && t == h1)));
+        }
+    }
 
     // Handle Start and End events
     for contact in raw.into_iter() {
         match &contact {
             PhysicsContact::Start { source_handle,target_handle, unpacked_source_id, unpacked_target_id, object_function } => {
-                result.push(contact.clone());
-                map.entry((*source_handle, *target_handle, *unpacked_source_id, *unpacked_target_id, *object_function)).or_insert(ContactState::default());
+                // a pair handed off together is already tracked; the new world re-reports it
+                let key = (region, *source_handle, *target_handle, *unpacked_source_id, *unpacked_target_id, *object_function);
+                if let Entry::Vacant(slot) = map.entry(key) {
+                    slot.insert(ContactState::default());
+                    result.push(contact.clone());
+                }
             }
             PhysicsContact::End { unpacked_source_id, unpacked_target_id, object_function, .. } => {
                 result.push(contact.clone());
                 let to_remove: Vec<_> = map.keys()
-                    .filter(|(_, _, sid, tid, func)| *sid == *unpacked_source_id && *tid == *unpacked_target_id && *func == *object_function)
+                    .filter(|(r, _, _, sid, tid, func)| *r == region && *sid == *unpacked_source_id && *tid == *unpacked_target_id && *func == *object_function)
                     .cloned()
                     .collect();
                 for key in to_remove {
@@ -150,7 +290,7 @@ pub fn process_contacts(
         }
     }
 
-    for ((source_handle, target_handle, sid, tid, object_function), state) in map.iter_mut() {
+    for ((_, source_handle, target_handle, sid, tid, object_function), state) in map.iter_mut().filter(|((r, ..), _)| *r == region) {
         state.tick_count = state.tick_count.saturating_add(1);
         result.push(PhysicsContact::Continue {
             source_handle: *source_handle,
@@ -168,21 +308,27 @@ pub fn process_contacts(
 pub fn handle_event(ctx: &ReducerContext, world: &mut PhysicsContext, contact: PhysicsContact) {
     match contact {
         PhysicsContact::Start { source_handle, target_handle, unpacked_source_id, unpacked_target_id, object_function } => {
-            if object_function == 2 {
+            // projectiles count every new contact against their hit budget
+            for (handle, id) in [(source_handle, unpacked_source_id), (target_handle, unpacked_target_id)] {
+                if world.colliders.get(handle).is_some_and(|c| get_body_type(c.user_data) == PROJECTILE_BODY_TYPE) {
+                    on_projectile_hit(ctx, world, id);
+                }
+            }
+            if object_function == object_function::AURA {
                 // apply aura buff and record its row ID
                 if let Some(pb) = ctx.db.physics_body().entity_id().find(unpacked_target_id) {
                     let player = pb.owner_id;
                     let expires = Timestamp::from_micros_since_unix_epoch(i64::MAX);
                     let buff_id = apply_buff(ctx, player, object_function, 1.0, expires);
                     // store buff_id in active-contact state
-                    let key = (source_handle, target_handle, unpacked_source_id, unpacked_target_id, object_function);
+                    let key = (world.region, source_handle, target_handle, unpacked_source_id, unpacked_target_id, object_function);
                     if let Some(state) = ACTIVE_CONTACTS.lock().unwrap().get_mut(&key) {
                         state.buff_id = Some(buff_id);
                     }
                 }
             }
          
-         let ce_id = CONTACT_EVENT_ID_COUNTER.fetch_add(1, Ordering::Relaxed);
+         let ce_id = next_id(ctx, Sequence::ContactEvent);
             let ev = ContactEvent { id: ce_id, entity_1: unpacked_source_id, entity_2: unpacked_target_id, started_at: ctx.timestamp };
             ctx.db.contact_event().insert(ev);
             log::debug!("Contact Start: src={}, tgt={}, func={}",
@@ -191,11 +337,11 @@ pub fn handle_event(ctx: &ReducerContext, world: &mut PhysicsContext, contact: P
          },
          #[allow(unused_variables)]
          PhysicsContact::Continue { source_handle, target_handle, unpacked_source_id, unpacked_target_id, object_function, tick_count } => {
-            if object_function == 1 {
+            if object_function == object_function::DAMAGE_OVER_TIME {
                 if tick_count % 5 == 0 {
                     log::debug!("5 ticks -> one hit");
                     // centralize damage: accumulate and emit event
-                    apply_damage(ctx, object_function, unpacked_target_id, 1);
+                    apply_damage_in(ctx, world, object_function, unpacked_target_id, 1);
 
                     if let Some(collider) = world.colliders.get_mut(source_handle) {
                         // increment collider userData hit count
@@ -206,17 +352,21 @@ pub fn handle_event(ctx: &ReducerContext, world: &mut PhysicsContext, contact: P
                             // Hits go over 30
                             ACTIVE_CONTACTS.lock().unwrap()
                                 //.remove(&(handle, option_id.clone(), source_id_u64, target_id_u64));
-                                .retain(|(h, _, _, _, _), _| *h != source_handle);
+                                .retain(|(r, h, _, _, _, _), _| *r != world.region || *h != source_handle);
                             log::debug!("Contact Continue: collider hit_count={} - removed all contact entries for handle {:?}", new_hits, source_handle);
+                            // a spent projectile is removed entirely, not just silenced
+                            if get_body_type(data) == PROJECTILE_BODY_TYPE {
+                                world.pending_despawns.push((unpacked_source_id, DespawnReason::HitLimit));
+                            }
                         }
                     }
                 }
             }
          },
          PhysicsContact::End { source_handle, target_handle, unpacked_source_id, unpacked_target_id, object_function } => {
-            if object_function == 2 {
+            if object_function == object_function::AURA {
                 // delete the specific aura buff instance recorded earlier
-                let key = (source_handle, target_handle, unpacked_source_id, unpacked_target_id, object_function);
+                let key = (world.region, source_handle, target_handle, unpacked_source_id, unpacked_target_id, object_function);
                 if let Some(state) = ACTIVE_CONTACTS.lock().unwrap().get(&key) {
                     if let Some(bid) = state.buff_id {
                         ctx.db.player_buffs().id().delete(bid);
@@ -224,8 +374,8 @@ pub fn handle_event(ctx: &ReducerContext, world: &mut PhysicsContext, contact: P
                 }
             }
 
-             if let Some(row) = ctx.db.contact_event().iter()
-                .find(|e| e.entity_1 == unpacked_source_id && e.entity_2 == unpacked_target_id)
+             if let Some(row) = ctx.db.contact_event().entity_1().filter(unpacked_source_id)
+                .find(|e| e.entity_2 == unpacked_target_id)
              {
                  ctx.db.contact_event().id().delete(row.id);
                  log::debug!("Contact End: src={}, tgt={}", unpacked_source_id, unpacked_target_id);
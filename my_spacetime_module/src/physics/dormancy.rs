@@ -0,0 +1,74 @@
+//! Region sleep and wake based on player presence.
+//!
+//! A region with no connected player's body in it for `REGION_SLEEP_AFTER_MICROS` goes
+//! dormant once nothing in it moves any more (no awake dynamic bodies, no live projectiles): its last tick persists the bodies as usual, then the in-memory world is dropped
+//! and no further tick is scheduled. Anything that needs the world again (`world_for_region`,
+//! a player reconnecting into the region) schedules a tick, and the world is rebuilt from
+//! `physics_body` rows on first use.
+use spacetimedb::{ReducerContext, Table};
+use crate::physics::contact_tracker::release_world_contacts;
+use crate::physics::rapier_common::{get_body_type, HashMap};
+use crate::physics::PhysicsContext;
+use crate::reducers::lifecycle::ensure_physics_tick_scheduled;
+use crate::spacetime_common::collision::PROJECTILE_BODY_TYPE;
+use crate::tables::physics_body::physics_body;
+use crate::tables::physics_tick_stats::physics_tick_stats;
+use crate::tables::player::{player, PlayerStatus};
+
+/// How long a region must be without players before it goes dormant (lets thrown and falling
+/// bodies come to rest first)
+pub const REGION_SLEEP_AFTER_MICROS: i64 = 5_000_000;
+
+/// True if a connected player has a body in the region
+pub fn region_has_players(ctx: &ReducerContext, region: u32) -> bool {
+    ctx.db.player().iter()
+        .filter(|p| p.status != PlayerStatus::Offline)
+        .any(|p| ctx.db.physics_body().entity_id().find(p.phy_entity_id).is_some_and(|b| b.region == region))
+}
+
+/// True if nothing in the world is moving: every dynamic body is asleep and no projectile is
+/// in flight (its lifetime and distance limits still need ticks)
+pub fn world_at_rest(world: &PhysicsContext) -> bool {
+    world.islands.active_dynamic_bodies().is_empty()
+        && !world.bodies.iter().any(|(_, b)| get_body_type(b.user_data) == PROJECTILE_BODY_TYPE)
+}
+
+/// Track how long the region has been empty; true once it may go dormant.
+/// Regions still working through deferred events or despawns, or not yet at rest, stay awake.
+pub(crate) fn ready_to_sleep(ctx: &ReducerContext, world: &mut PhysicsContext, region: u32) -> bool {
+    if region_has_players(ctx, region) {
+        world.idle_since = None;
+        return false;
+    }
+    let idle_since = *world.idle_since.get_or_insert(ctx.timestamp);
+    let idle_micros = ctx.timestamp.to_micros_since_unix_epoch() - idle_since.to_micros_since_unix_epoch();
+    idle_micros >= REGION_SLEEP_AFTER_MICROS
+        && world.pending_events.is_empty()
+        && world.pending_despawns.is_empty()
+        && world_at_rest(world)
+}
+
+/// Unload a region's world after its final tick; the caller must not schedule another tick
+pub(crate) fn sleep_region(ctx: &ReducerContext, contexts: &mut HashMap<u32, PhysicsContext>, region: u32) {
+    let Some(mut world) = contexts.remove(&region) else { return };
+    release_world_contacts(ctx, &mut world);
+    if let Some(mut stats) = ctx.db.physics_tick_stats().region().find(region) {
+        stats.dormant_since = Some(ctx.timestamp);
+        ctx.db.physics_tick_stats().region().update(stats);
+    }
+    log::info!("Region {} is dormant ({} bodies persisted)", region, world.bodies.len());
+}
+
+/// Restart the tick loop of a dormant region (no-op if it is already ticking)
+pub fn wake_region(ctx: &ReducerContext, region: u32) {
+    if let Err(e) = ensure_physics_tick_scheduled(ctx, region) {
+        log::error!("Failed to wake region {}: {}", region, e);
+    }
+}
+
+/// Wake the region a player's body is in, e.g. when they connect
+pub fn wake_player_region(ctx: &ReducerContext, phy_entity_id: u32) {
+    if let Some(body) = ctx.db.physics_body().entity_id().find(phy_entity_id) {
+        wake_region(ctx, body.region);
+    }
+}
@@ -0,0 +1,170 @@
+//! Cross-region body handoff: moves bodies that crossed a region border into the neighbouring world
+use crate::physics::rapier_common::*;
+use rapier3d::prelude::*;
+use spacetimedb::{ReducerContext, Table, Timestamp};
+use crate::tables::physics_body::physics_body;
+use crate::tables::region_config::{region_config, RegionConfig};
+use crate::physics::contact_tracker::{end_split_contacts, move_collider_owner, move_contacts};
+use crate::physics::character::CharacterState;
+use crate::physics::{chunk_of, world_for_region, PhysicsContext};
+use crate::reducers::lifecycle::ensure_physics_tick_scheduled;
+
+/// Region that owns chunks not claimed by any configured chunk range
+pub const DEFAULT_REGION: u32 = 0;
+
+/// A body removed from one world together with everything needed to re-insert it elsewhere
+pub(crate) struct DetachedBody {
+    entity_id: u32,
+    /// Region whose world the body left
+    region: u32,
+    body: RigidBody,
+    colliders: Vec<(ColliderHandle, Collider)>,
+    pending_damage: Option<u32>,
+    character: Option<CharacterState>,
+    /// Invulnerability expiry; the colliders' block bit travels in their user data
+    invulnerable_until: Option<Timestamp>,
+    /// Whether a force added this tick is still waiting to be cleared
+    forced: bool,
+}
+
+/// Region owning a chunk according to the configured layout
+fn region_for_chunk(layout: &[RegionConfig], chunk_x: i32, chunk_y: i32) -> u32 {
+    layout.iter()
+        .find(|c| c.contains_chunk(chunk_x, chunk_y))
+        .map_or(DEFAULT_REGION, |c| c.region)
+}
+
+/// Move every body of `region` that now lies in another region's chunk range into that region.
+/// Velocity, user data, pending damage, invulnerability and tick forces travel with the body.
+/// Contacts with bodies moving into the same region carry on there; contacts with bodies
+/// left behind are ended (aura buffs and contact_event rows go with them), since the two
+/// worlds share no narrow phase that could ever report them stopped.
+pub fn migrate_bodies(ctx: &ReducerContext, contexts: &mut HashMap<u32, PhysicsContext>, region: u32) {
+    let layout: Vec<RegionConfig> = ctx.db.region_config().iter().collect();
+    if layout.is_empty() {
+        // no layout configured: everything lives in the default region
+        return;
+    }
+
+    let Some(world) = contexts.get_mut(&region) else { return };
+    let mut departures = Vec::new();
+    for (handle, body) in world.bodies.iter() {
+        if body.is_fixed() {
+            continue;
+        }
+        let pos = body.translation();
+        let (chunk_x, chunk_y) = chunk_of(pos);
+        let target = region_for_chunk(&layout, chunk_x, chunk_y);
+        if target == region {
+            continue;
+        }
+        // joints can't span worlds, so jointed bodies stay until their joints are removed
+        if world.impulse_joints.attached_joints(handle).next().is_some() {
+            log::debug!("Body {} crossed into region {} but is jointed; not migrating", unpack_id(body.user_data), target);
+            continue;
+        }
+        departures.push((handle, target));
+    }
+
+    let targets: HashMap<u32, u32> = departures.iter()
+        .filter_map(|(handle, target)| world.bodies.get(*handle).map(|b| (unpack_id(b.user_data), *target)))
+        .collect();
+    end_split_contacts(ctx, world, &targets);
+    let mut detached = Vec::with_capacity(departures.len());
+    for (handle, target) in departures {
+        if let Some(d) = detach_body(world, handle) {
+            detached.push((d, target));
+        }
+    }
+
+    // old -> new collider handles per target region, to re-key the contacts that moved along
+    let mut moved: HashMap<u32, HashMap<ColliderHandle, ColliderHandle>> = HashMap::new();
+    for (detached, target) in detached {
+        let entity_id = detached.entity_id;
+        let handles = attach_body(world_for_region(ctx, contexts, target), detached);
+        moved.entry(target).or_default().extend(handles);
+
+        if let Some(mut row) = ctx.db.physics_body().entity_id().find(entity_id) {
+            row.region = target;
+            ctx.db.physics_body().entity_id().update(row);
+        }
+        if let Err(e) = ensure_physics_tick_scheduled(ctx, target) {
+            log::error!("Failed to schedule physics tick for region {}: {}", target, e);
+        }
+        log::info!("Physics body {} handed off from region {} to region {}", entity_id, region, target);
+    }
+    for (target, handles) in moved {
+        move_contacts(region, target, &handles);
+    }
+}
+
+/// Remove a body and its colliders from a world without dropping them
+pub(crate) fn detach_body(world: &mut PhysicsContext, handle: RigidBodyHandle) -> Option<DetachedBody> {
+    let body = world.bodies.get(handle)?;
+    let entity_id = unpack_id(body.user_data);
+    let collider_handles = body.colliders().to_vec();
+
+    let mut colliders = Vec::with_capacity(collider_handles.len());
+    for col_handle in collider_handles {
+        if let Some(col) = world.colliders.remove(col_handle, &mut world.islands, &mut world.bodies, false) {
+            colliders.push((col_handle, col));
+        }
+    }
+    let body = world.bodies.remove(
+        handle,
+        &mut world.islands,
+        &mut world.colliders,
+        &mut world.impulse_joints,
+        &mut world.multibody_joints,
+        false,
+    )?;
+
+    world.id_to_body.remove(&entity_id);
+    // freed handles get reused, so rewinds must not move a later collider to these poses
+    for snapshot in world.history.iter_mut() {
+        snapshot.poses.retain(|(h, _)| colliders.iter().all(|(c, _)| c != h));
+    }
+    world.last_transforms.remove(&handle);
+    world.last_velocities.remove(&handle);
+    let pending_damage = world.pending_damage.remove(&entity_id);
+    let character = world.characters.remove(&entity_id);
+    let invulnerable_until = world.invulnerable_until.remove(&entity_id);
+    let forced = world.forced_bodies.contains(&handle);
+    world.forced_bodies.retain(|h| *h != handle);
+
+    Some(DetachedBody { entity_id, region: world.region, body, colliders, pending_damage, character, invulnerable_until, forced })
+}
+
+/// Insert a detached body into a world and re-point collider owners at its new handles;
+/// returns the (old, new) handle of each collider
+pub(crate) fn attach_body(world: &mut PhysicsContext, detached: DetachedBody) -> Vec<(ColliderHandle, ColliderHandle)> {
+    let DetachedBody { entity_id, region, body, colliders, pending_damage, character, invulnerable_until, forced } = detached;
+    let transform = (*body.translation(), *body.rotation());
+    let velocity = (*body.linvel(), *body.angvel());
+
+    let handle = world.bodies.insert(body);
+    world.id_to_body.insert(entity_id, handle);
+    let mut handles = Vec::with_capacity(colliders.len());
+    for (old_handle, col) in colliders {
+        let new_handle = world.colliders.insert_with_parent(col, handle, &mut world.bodies);
+        move_collider_owner((region, old_handle), (world.region, new_handle));
+        handles.push((old_handle, new_handle));
+    }
+
+    // the row was written this tick, so seed the change trackers with the current state
+    world.last_transforms.insert(handle, transform);
+    world.last_velocities.insert(handle, velocity);
+    if let Some(character) = character {
+        world.characters.insert(entity_id, character);
+    }
+    if let Some(dmg) = pending_damage {
+        *world.pending_damage.entry(entity_id).or_insert(0) += dmg;
+    }
+    if let Some(until) = invulnerable_until {
+        world.invulnerable_until.insert(entity_id, until);
+    }
+    if forced {
+        world.forced_bodies.push(handle);
+    }
+    handles
+}
@@ -0,0 +1,188 @@
+//! Rapier physics hooks driven by the packed collider `UserData`.
+//!
+//! Rules live in two registries: `CONTACT_RULES`, keyed by the `object_function` of the
+//! collider that owns the rule (one-way platforms, conveyors), and `GLOBAL_RULES`, applied
+//! to every pair (ghosting through allies via `flag` + `modifier` as team, invulnerability
+//! via `block`). Every rule sees the pair from both sides.
+use crate::physics::rapier_common::*;
+use rapier3d::prelude::*;
+use spacetimedb::{reducer, ReducerContext, Timestamp};
+use crate::physics::{world_for_region, PhysicsContext, PHYSICS_CONTEXTS};
+use crate::reducers::auth::require_admin;
+use crate::spacetime_common::collision::object_function;
+use crate::tables::physics_body::physics_body;
+
+/// Steepest approach (from the platform's local +Y) that still lands on a one-way platform
+pub const ONE_WAY_ALLOWED_ANGLE: Real = 0.7; // ~40°
+/// Conveyor surface speed per unit of `modifier`, along the collider's local +X
+pub const CONVEYOR_SPEED_STEP: Real = 0.1;
+/// Friction used on conveyor contacts so bodies are carried along
+pub const CONVEYOR_FRICTION: Real = 1.0;
+
+/// A colliding pair as seen from the collider whose rule is being applied
+pub struct PairView<'a> {
+    pub own: &'a Collider,
+    pub other: &'a Collider,
+    /// `own` is Rapier's `collider1` (normals point from it to `other`)
+    pub own_is_first: bool,
+}
+
+/// Contact rule behaviour; every method defaults to "no opinion"
+trait ContactRule: Sync + Send + 'static {
+    /// false drops solid contacts between the pair for this step
+    fn allow_contact(&self, _pair: &PairView) -> bool { true }
+    /// false ignores a sensor overlap (and with it auras / damage over time)
+    fn allow_intersection(&self, _pair: &PairView) -> bool { true }
+    /// Adjust the pair's solver contacts (friction, surface velocity, ...)
+    fn modify(&self, _pair: &PairView, _ctx: &mut ContactModificationContext) {}
+}
+
+/// Colliders with `flag` set pass through colliders of the same team (`modifier`) that also have it
+struct AllyGhosting;
+impl ContactRule for AllyGhosting {
+    fn allow_contact(&self, pair: &PairView) -> bool {
+        !ghosts(pair.own, pair.other)
+    }
+}
+
+/// Colliders with `block` set don't trigger sensors
+struct Invulnerability;
+impl ContactRule for Invulnerability {
+    fn allow_intersection(&self, pair: &PairView) -> bool {
+        !get_block(pair.own.user_data)
+    }
+}
+
+/// Solid only when approached from above its local +Y
+struct OneWayPlatform;
+impl ContactRule for OneWayPlatform {
+    fn modify(&self, pair: &PairView, ctx: &mut ContactModificationContext) {
+        // the allowed normal points from collider1 to collider2, in collider1's local frame
+        let allowed = if pair.own_is_first {
+            Vector::y()
+        } else {
+            pair.other.rotation().inverse() * -(pair.own.rotation() * Vector::y())
+        };
+        ctx.update_as_oneway_platform(&allowed, ONE_WAY_ALLOWED_ANGLE);
+    }
+}
+
+/// Carries touching bodies along its local +X at `modifier * CONVEYOR_SPEED_STEP`
+struct Conveyor;
+impl ContactRule for Conveyor {
+    fn modify(&self, pair: &PairView, ctx: &mut ContactModificationContext) {
+        let speed = get_modifier(pair.own.user_data) as Real * CONVEYOR_SPEED_STEP;
+        let dir = pair.own.rotation() * Vector::x();
+        // tangent velocity is the surface velocity of collider1 relative to collider2
+        let velocity = if pair.own_is_first { dir * speed } else { -dir * speed };
+        for contact in ctx.solver_contacts.iter_mut() {
+            contact.tangent_velocity = velocity;
+            contact.friction = CONVEYOR_FRICTION;
+        }
+    }
+}
+
+static GLOBAL_RULES: Lazy<Vec<Box<dyn ContactRule>>> = Lazy::new(|| {
+    vec![Box::new(AllyGhosting), Box::new(Invulnerability)]
+});
+
+// Registry of rules by object_function
+static CONTACT_RULES: Lazy<HashMap<u8, Box<dyn ContactRule>>> = Lazy::new(|| {
+    let mut m: HashMap<u8, Box<dyn ContactRule>> = HashMap::new();
+    m.insert(object_function::ONE_WAY_PLATFORM, Box::new(OneWayPlatform));
+    m.insert(object_function::CONVEYOR, Box::new(Conveyor));
+    m
+});
+
+/// True if `a` ghosts through `b` (same team, both opted in)
+pub fn ghosts(a: &Collider, b: &Collider) -> bool {
+    get_flag(a.user_data) && get_flag(b.user_data) && get_modifier(a.user_data) == get_modifier(b.user_data)
+}
+
+/// True if a character moving with `own` should ignore `other`: ghosted allies, and
+/// one-way platforms it is still below
+pub fn passes_through(own: &Collider, other: &Collider) -> bool {
+    if ghosts(own, other) {
+        return true;
+    }
+    get_object_function(other.user_data) == object_function::ONE_WAY_PLATFORM && {
+        let up = other.rotation() * Vector::y();
+        (own.translation() - other.translation()).dot(&up) < 0.0
+    }
+}
+
+/// Call `f` with every applicable rule and the pair seen from that rule's collider
+fn for_each_rule(c1: &Collider, c2: &Collider, mut f: impl FnMut(&dyn ContactRule, &PairView)) {
+    for (own, other, own_is_first) in [(c1, c2, true), (c2, c1, false)] {
+        let pair = PairView { own, other, own_is_first };
+        for rule in GLOBAL_RULES.iter() {
+            f(rule.as_ref(), &pair);
+        }
+        if let Some(rule) = CONTACT_RULES.get(&get_object_function(own.user_data)) {
+            f(rule.as_ref(), &pair);
+        }
+    }
+}
+
+/// Hooks passed to `PhysicsPipeline::step`
+pub struct GameHooks;
+
+impl PhysicsHooks for GameHooks {
+    fn filter_contact_pair(&self, context: &PairFilterContext) -> Option<SolverFlags> {
+        let (c1, c2) = (&context.colliders[context.collider1], &context.colliders[context.collider2]);
+        let mut allowed = true;
+        for_each_rule(c1, c2, |rule, pair| allowed &= rule.allow_contact(pair));
+        allowed.then_some(SolverFlags::COMPUTE_IMPULSES)
+    }
+
+    fn filter_intersection_pair(&self, context: &PairFilterContext) -> bool {
+        let (c1, c2) = (&context.colliders[context.collider1], &context.colliders[context.collider2]);
+        let mut allowed = true;
+        for_each_rule(c1, c2, |rule, pair| allowed &= rule.allow_intersection(pair));
+        allowed
+    }
+
+    fn modify_solver_contacts(&self, context: &mut ContactModificationContext) {
+        let colliders = context.colliders;
+        let (c1, c2) = (&colliders[context.collider1], &colliders[context.collider2]);
+        for_each_rule(c1, c2, |rule, pair| rule.modify(pair, context));
+    }
+}
+
+/// Set or clear the `block` bit on all of an entity's colliders
+pub fn set_invulnerable(world: &mut PhysicsContext, entity_id: u32, on: bool) {
+    let Some(&handle) = world.id_to_body.get(&entity_id) else { return };
+    let Some(body) = world.bodies.get(handle) else { return };
+    for &col in body.colliders() {
+        if let Some(col) = world.colliders.get_mut(col) {
+            col.user_data = set_block(col.user_data, on);
+        }
+    }
+}
+
+/// Clear invulnerability that ran out before `now`
+pub(crate) fn expire_invulnerability(world: &mut PhysicsContext, now: Timestamp) {
+    let expired: Vec<u32> = world.invulnerable_until.iter()
+        .filter(|(_, until)| **until <= now)
+        .map(|(id, _)| *id)
+        .collect();
+    for entity_id in expired {
+        world.invulnerable_until.remove(&entity_id);
+        set_invulnerable(world, entity_id, false);
+    }
+}
+
+#[reducer]
+/// Make an entity invulnerable for `duration_ms` (admin only)
+pub fn grant_invulnerability(ctx: &ReducerContext, entity_id: u32, duration_ms: u32) -> Result<(), String> {
+    require_admin(ctx)?;
+    let row = ctx.db.physics_body().entity_id().find(entity_id).ok_or("Body not found")?;
+    let until = Timestamp::from_micros_since_unix_epoch(
+        ctx.timestamp.to_micros_since_unix_epoch() + duration_ms as i64 * 1000
+    );
+    let mut map = PHYSICS_CONTEXTS.lock().unwrap();
+    let world = world_for_region(ctx, &mut map, row.region);
+    set_invulnerable(world, entity_id, true);
+    world.invulnerable_until.insert(entity_id, until);
+    Ok(())
+}
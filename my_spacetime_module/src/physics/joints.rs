@@ -0,0 +1,132 @@
+use rapier3d::prelude::*;
+use spacetimedb::{reducer, ReducerContext, Table};
+use crate::tables::physics_body::physics_body;
+use crate::tables::physics_joint::{physics_joint, JointKind, PhysicsJoint};
+use crate::reducers::auth::require_admin;
+use crate::physics::{world_for_region, PhysicsContext, PHYSICS_CONTEXTS};
+
+/// Validate and build the Rapier joint described by a `physics_joint` row
+pub(crate) fn build_joint(row: &PhysicsJoint) -> Result<GenericJoint, String> {
+    row.validate()?;
+    let anchor_a = point![row.anchor_a_x, row.anchor_a_y, row.anchor_a_z];
+    let anchor_b = point![row.anchor_b_x, row.anchor_b_y, row.anchor_b_z];
+    let axis = || {
+        UnitVector::try_new(vector![row.axis_x, row.axis_y, row.axis_z], 1.0e-6)
+            .ok_or_else(|| "Joint axis must be non-zero".to_string())
+    };
+
+    let mut joint: GenericJoint = match row.kind {
+        JointKind::Fixed => FixedJointBuilder::new().into(),
+        JointKind::Revolute => RevoluteJointBuilder::new(axis()?).into(),
+        JointKind::Prismatic => PrismaticJointBuilder::new(axis()?).into(),
+        JointKind::Spherical => SphericalJointBuilder::new().into(),
+        JointKind::Rope => {
+            let length = row.limits.map(|l| l.max)
+                .ok_or_else(|| "Rope joints need limits.max as their length".to_string())?;
+            RopeJointBuilder::new(length).into()
+        }
+    };
+    joint.set_local_anchor1(anchor_a)
+        .set_local_anchor2(anchor_b)
+        .set_contacts_enabled(row.contacts_enabled);
+
+    // limits & motors act on the single free axis of hinge/slider joints
+    let free_axis = match row.kind {
+        JointKind::Revolute => Some(JointAxis::AngX),
+        JointKind::Prismatic => Some(JointAxis::LinX),
+        JointKind::Rope => Some(JointAxis::LinX),
+        JointKind::Fixed | JointKind::Spherical => None,
+    };
+    if let Some(free_axis) = free_axis {
+        // rope length was already taken from limits.max
+        if let Some(limits) = row.limits.filter(|_| row.kind != JointKind::Rope) {
+            joint.set_limits(free_axis, [limits.min, limits.max]);
+        }
+        if let Some(motor) = row.motor {
+            joint.set_motor(free_axis, motor.target_pos, motor.target_vel, motor.stiffness, motor.damping)
+                .set_motor_max_force(free_axis, motor.max_force);
+        }
+    } else if row.motor.is_some() {
+        return Err(format!("{:?} joints don't support motors", row.kind));
+    }
+    Ok(joint)
+}
+
+/// Insert the joint described by a row into a world, tracking it in `id_to_joint`
+pub(crate) fn insert_joint(world: &mut PhysicsContext, row: &PhysicsJoint) -> Result<ImpulseJointHandle, String> {
+    let joint = build_joint(row)?;
+    let body_a = *world.id_to_body.get(&row.body_a).ok_or("Joint body_a not found in region")?;
+    let body_b = *world.id_to_body.get(&row.body_b).ok_or("Joint body_b not found in region")?;
+    let handle = world.impulse_joints.insert(body_a, body_b, joint, true);
+    world.id_to_joint.insert(row.joint_id, handle);
+    Ok(handle)
+}
+
+/// Delete the `physics_joint` rows attached to an entity (Rapier drops the joints with the body)
+pub(crate) fn delete_joints_of(ctx: &ReducerContext, world: Option<&mut PhysicsContext>, entity_id: u32) {
+    let rows: Vec<_> = ctx.db.physics_joint().body_a().filter(entity_id)
+        .chain(ctx.db.physics_joint().body_b().filter(entity_id))
+        .collect();
+    let mut world = world;
+    for row in rows {
+        if let Some(world) = world.as_deref_mut() {
+            world.id_to_joint.remove(&row.joint_id);
+        }
+        ctx.db.physics_joint().joint_id().delete(row.joint_id);
+    }
+}
+
+#[reducer]
+/// Create a joint between two bodies of the same region.
+/// `joint_id`, `region` and `owner_id` of the passed row are assigned by the server.
+pub fn create_joint(ctx: &ReducerContext, joint: PhysicsJoint) -> Result<(), String> {
+    if joint.body_a == joint.body_b {
+        return Err("Cannot joint a body to itself".into());
+    }
+    let a = ctx.db.physics_body().entity_id().find(joint.body_a).ok_or("body_a not found")?;
+    let b = ctx.db.physics_body().entity_id().find(joint.body_b).ok_or("body_b not found")?;
+    if a.region != b.region {
+        return Err("Jointed bodies must be in the same region".into());
+    }
+    // level geometry is owned by whoever spawned it; admins may joint anything
+    if (a.owner_id != ctx.sender || b.owner_id != ctx.sender) && require_admin(ctx).is_err() {
+        return Err("You don't own both bodies".into());
+    }
+
+    // validate by building before anything is persisted
+    build_joint(&joint)?;
+    let row = ctx.db.physics_joint().insert(PhysicsJoint {
+        joint_id: 0,
+        region: a.region,
+        owner_id: ctx.sender,
+        ..joint
+    });
+
+    let mut map = PHYSICS_CONTEXTS.lock().unwrap();
+    let world = world_for_region(ctx, &mut map, row.region);
+    // a freshly hydrated world already picked the new row up
+    if !world.id_to_joint.contains_key(&row.joint_id) {
+        insert_joint(world, &row)?;
+    }
+    log::info!("Joint {} ({:?}) created between {} and {}", row.joint_id, row.kind, row.body_a, row.body_b);
+    Ok(())
+}
+
+#[reducer]
+/// Remove a joint from its world and delete its row
+pub fn remove_joint(ctx: &ReducerContext, joint_id: u64) -> Result<(), String> {
+    let row = ctx.db.physics_joint().joint_id().find(joint_id).ok_or("Joint not found")?;
+    if row.owner_id != ctx.sender {
+        require_admin(ctx)?;
+    }
+
+    let mut map = PHYSICS_CONTEXTS.lock().unwrap();
+    if let Some(world) = map.get_mut(&row.region)
+        && let Some(handle) = world.id_to_joint.remove(&joint_id)
+    {
+        world.impulse_joints.remove(handle, true);
+    }
+    ctx.db.physics_joint().joint_id().delete(joint_id);
+    log::info!("Joint {} removed", joint_id);
+    Ok(())
+}
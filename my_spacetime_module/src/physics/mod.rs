@@ -4,14 +4,31 @@ use rapier3d::prelude::*;
 //use nalgebra::UnitQuaternion;
 use rapier3d::na::UnitQuaternion;
 use crossbeam::channel::Receiver;
-use spacetimedb::ReducerContext;
+use spacetimedb::{ReducerContext, Timestamp};
 use crate::tables::physics_body::physics_body;
+use crate::tables::physics_joint::physics_joint;
+use crate::tables::region_config::{region_config, RegionConfig};
+use crate::tables::despawn_event::DespawnReason;
+use std::num::NonZeroUsize;
+use std::collections::{HashSet, VecDeque};
+use rapier3d::control::KinematicCharacterController;
+use crate::physics::character::{make_controller, CharacterState};
 
 pub mod contact_tracker;
 pub mod spawn;
 pub mod physics_tick;
 pub mod rapier_common;
 pub mod skills;
+pub mod handoff;
+pub mod joints;
+pub mod character;
+pub mod motion;
+pub mod projectile;
+pub mod queries;
+pub mod assets;
+pub mod hooks;
+pub mod rewind;
+pub mod dormancy;
 
 
 // Forward old calls to the new spawn.rs
@@ -22,6 +39,8 @@ pub mod tests;
 
 /// Physics world state for a region
 pub struct PhysicsContext {
+    /// Region this world simulates; collider handles are only unique within it
+    pub region: u32,
     pub pipeline: PhysicsPipeline,
     pub query_pipeline: QueryPipeline,
     /// Accumulated damage per entity raw_id for batched DB writes
@@ -36,16 +55,45 @@ pub struct PhysicsContext {
     pub impulse_joints: ImpulseJointSet,
     pub multibody_joints: MultibodyJointSet,
     pub ccd_solver: CCDSolver,
+    /// Shared controller resolving player movement against the world
+    pub character_controller: KinematicCharacterController,
+    /// Movement state per character entity raw_id
+    pub characters: HashMap<u32, CharacterState>,
+    /// Collision events not yet handled because a tick ran out of event budget (FIFO, never dropped)
+    pub pending_events: VecDeque<CollisionEvent>,
+    /// Entities to despawn (and why) once this tick's contacts were handled
+    pub pending_despawns: Vec<(u32, DespawnReason)>,
+    /// Invulnerable entity raw_id → expiry; their colliders carry the `block` bit meanwhile
+    pub invulnerable_until: HashMap<u32, Timestamp>,
+    /// Collider transforms of recent ticks for lag-compensated queries (see `rewind`)
+    pub history: VecDeque<rewind::TransformSnapshot>,
+    /// How far back `history` reaches, from `RegionConfig::max_rewind_micros`
+    pub max_rewind_micros: i64,
+    /// Bodies with forces from `add_force_to`, reset after the next stepped tick
+    pub forced_bodies: Vec<RigidBodyHandle>,
     // Track last known transform to minimize DB updates per tick
     pub last_transforms: HashMap<RigidBodyHandle, (Vector<Real>, UnitQuaternion<Real>)>,
+    /// Bodies in Rapier's active islands at the last DB write; ones that have fallen asleep
+    /// since get a final write of their resting state
+    pub awake_bodies: Vec<RigidBodyHandle>,
+    // Track last written (linear, angular) velocity so jitter below VELOCITY_EPSILON is not persisted
+    pub last_velocities: HashMap<RigidBodyHandle, (Vector<Real>, Vector<Real>)>,
     // Map raw 32-bit physics entity ID → RigidBodyHandle for O(1) forward lookup
     pub id_to_body: HashMap<u32, RigidBodyHandle>,
-
+    // Map physics_joint.joint_id → ImpulseJointHandle
+    pub id_to_joint: HashMap<u64, ImpulseJointHandle>,
+    /// Simulated time owed to the world (seconds), consumed in fixed `integration_parameters.dt` steps
+    pub accumulator: Real,
+    /// Timestamp of the previous physics_tick for this region
+    pub last_tick_at: Option<Timestamp>,
+    /// Since when the region has had no connected player (see `dormancy`)
+    pub idle_since: Option<Timestamp>,
 }
 
 impl Default for PhysicsContext {
     fn default() -> Self {
         PhysicsContext {
+            region: 0,
             pipeline: PhysicsPipeline::new(),
             query_pipeline: QueryPipeline::new(),
             pending_damage: HashMap::new(),
@@ -59,31 +107,213 @@ impl Default for PhysicsContext {
             impulse_joints: ImpulseJointSet::new(),
             multibody_joints: MultibodyJointSet::new(),
             ccd_solver: CCDSolver::new(),
+            character_controller: make_controller(&vector![0.0, -9.81, 0.0]),
+            characters: HashMap::new(),
+            pending_events: VecDeque::new(),
+            pending_despawns: Vec::new(),
+            invulnerable_until: HashMap::new(),
+            history: VecDeque::new(),
+            max_rewind_micros: rewind::MAX_REWIND_MICROS,
+            forced_bodies: Vec::new(),
             last_transforms: HashMap::new(),
+            last_velocities: HashMap::new(),
+            awake_bodies: Vec::new(),
             id_to_body: HashMap::new(),
+            id_to_joint: HashMap::new(),
+            accumulator: 0.0,
+            last_tick_at: None,
+            idle_since: None,
         }
     }
 }
 
+/// Map chunk of a body position: the chunk grid covers the ground plane (x, z), y is up
+pub fn chunk_of(pos: &Vector<Real>) -> (i32, i32) {
+    calculate_chunk_pair(pos.x, pos.z)
+}
+
+/// Minimum per-component velocity change (m/s, rad/s) that triggers a physics_body write
+pub const VELOCITY_EPSILON: Real = 0.01;
+/// Minimum per-component position change (m) that triggers a physics_body write
+pub const POSITION_EPSILON: Real = 0.001;
+/// Minimum rotation change (radians) that triggers a physics_body write
+pub const ROTATION_EPSILON: Real = 0.001;
+
+impl PhysicsContext {
+    /// Apply a region's configuration; safe to call every tick
+    pub fn apply_config(&mut self, config: &RegionConfig) {
+        self.gravity = vector![config.gravity_x, config.gravity_y, config.gravity_z];
+        self.character_controller.up = make_controller(&self.gravity).up;
+        self.integration_parameters.num_solver_iterations =
+            NonZeroUsize::new(config.solver_iterations as usize).unwrap_or(NonZeroUsize::MIN);
+        self.integration_parameters.max_ccd_substeps = config.max_ccd_substeps as usize;
+        self.integration_parameters.min_ccd_dt = config.min_ccd_dt;
+        self.max_rewind_micros = config.max_rewind_micros;
+    }
+}
+
+/// Load a region's configuration, falling back to the built-in defaults
+pub fn load_region_config(ctx: &ReducerContext, region: u32) -> RegionConfig {
+    ctx.db.region_config().region().find(region).unwrap_or_else(|| RegionConfig::defaults(region))
+}
+
 pub static PHYSICS_CONTEXTS: Lazy<Mutex<HashMap<u32, PhysicsContext>>> =
     Lazy::new(|| Mutex::new(HashMap::new()));
 
+/// Get the physics world for a region, rebuilding it from `physics_body` rows if it is
+/// not in memory yet (first use after a publish, republish or host restart, or a dormant
+/// region) and making sure the region ticks again
+pub fn world_for_region<'a>(
+    ctx: &ReducerContext,
+    contexts: &'a mut HashMap<u32, PhysicsContext>,
+    region: u32,
+) -> &'a mut PhysicsContext {
+    if !contexts.contains_key(&region) {
+        dormancy::wake_region(ctx, region);
+    }
+    loaded_world(ctx, contexts, region)
+}
+
+/// True if `region` is a region clients may address: the default region, or one that is
+/// loaded, configured in `region_config` or has `physics_body` rows
+pub fn region_exists(ctx: &ReducerContext, contexts: &HashMap<u32, PhysicsContext>, region: u32) -> bool {
+    region == handoff::DEFAULT_REGION
+        || contexts.contains_key(&region)
+        || ctx.db.region_config().region().find(region).is_some()
+        || ctx.db.physics_body().region().filter(region).next().is_some()
+}
+
+/// `world_for_region` for a region ID supplied by a client: fails instead of creating a
+/// world (and a tick loop) for a region that doesn't exist
+pub fn existing_world<'a>(
+    ctx: &ReducerContext,
+    contexts: &'a mut HashMap<u32, PhysicsContext>,
+    region: u32,
+) -> Result<&'a mut PhysicsContext, String> {
+    if !region_exists(ctx, contexts, region) {
+        return Err(format!("Region {} does not exist", region));
+    }
+    Ok(world_for_region(ctx, contexts, region))
+}
+
+/// `world_for_region` without waking the region, for the region's own tick
+pub(crate) fn loaded_world<'a>(
+    ctx: &ReducerContext,
+    contexts: &'a mut HashMap<u32, PhysicsContext>,
+    region: u32,
+) -> &'a mut PhysicsContext {
+    contexts.entry(region).or_insert_with(|| {
+        let mut world = PhysicsContext { region, ..PhysicsContext::default() };
+        world.apply_config(&load_region_config(ctx, region));
+        hydrate_world(ctx, &mut world, region);
+        world
+    })
+}
+
+/// Rebuild bodies, colliders, packed user data and the `id_to_body` map from the DB
+fn hydrate_world(ctx: &ReducerContext, world: &mut PhysicsContext, region: u32) {
+    let mut restored = 0;
+    for row in ctx.db.physics_body().region().filter(region) {
+        let res = assets::BodyResources::load(ctx, &row);
+        match spawn::insert_body(world, &row, &res) {
+            Ok(handle) => {
+                // seed last transform with the persisted one so unchanged bodies are not rewritten
+                if let Some(body) = world.bodies.get(handle) {
+                    world.last_transforms.insert(handle, (*body.translation(), *body.rotation()));
+                    world.last_velocities.insert(handle, (*body.linvel(), *body.angvel()));
+                }
+                restored += 1;
+            }
+            Err(e) => log::error!("Failed to restore physics body {} in region {}: {}", row.entity_id, region, e),
+        }
+    }
+    projectile::restore_colliders(ctx, world);
+    // joints need both bodies in place, so restore them last
+    for row in ctx.db.physics_joint().region().filter(region) {
+        if let Err(e) = joints::insert_joint(world, &row) {
+            log::error!("Failed to restore joint {} in region {}: {}", row.joint_id, region, e);
+        }
+    }
+    // make the restored colliders visible to scene queries before the first step
+    world.query_pipeline.update(&world.colliders);
+    if restored > 0 {
+        log::info!("Restored {} physics bodies for region {}", restored, region);
+    }
+}
+
 
-// Drain all collision events from a channel into a Vec
-fn drain_collision_events(rx: &Receiver<rapier3d::geometry::CollisionEvent>) -> Vec<rapier3d::geometry::CollisionEvent> {
-    let mut events = Vec::new();
+// Move every collision event from the channel to the back of the world's pending queue
+fn drain_collision_events(rx: &Receiver<CollisionEvent>, pending: &mut VecDeque<CollisionEvent>) {
     while let Ok(ev) = rx.try_recv() {
-        events.push(ev);
+        pending.push_back(ev);
     }
-    events
 }
 
-fn apply_database_updates(ctx: &ReducerContext, world: &mut PhysicsContext) {
+/// Queue this tick's events behind any backlog and take at most `budget` of them, oldest
+/// first; the rest stay in `pending` for the next tick
+fn take_collision_events(rx: &Receiver<CollisionEvent>, pending: &mut VecDeque<CollisionEvent>, budget: usize) -> Vec<CollisionEvent> {
+    drain_collision_events(rx, pending);
+    let take = pending.len().min(budget);
+    pending.drain(..take).collect()
+}
+
+/// Advance the simulation by one fixed `integration_parameters.dt` step
+fn step_world(world: &mut PhysicsContext, events: &dyn EventHandler) {
+    world.pipeline.step(
+        &world.gravity,
+        &world.integration_parameters,
+        &mut world.islands,
+        &mut world.broad_phase,
+        &mut world.narrow_phase,
+        &mut world.bodies,
+        &mut world.colliders,
+        &mut world.impulse_joints,
+        &mut world.multibody_joints,
+        &mut world.ccd_solver,
+        None,
+        &hooks::GameHooks,
+        events,
+    );
+}
+
+/// Rows written and skipped by one `apply_database_updates` call
+#[derive(Clone, Copy, Debug, Default)]
+pub struct WriteStats {
+    pub written: u32,
+    /// Non-fixed bodies left untouched (asleep, or changed by less than the epsilons)
+    pub skipped: u32,
+}
+
+/// Bodies whose rows may need writing: Rapier's active islands, bodies that fell asleep since
+/// the last write, bodies with pending damage and characters whose grounded flag flipped.
+/// Everything else is asleep and unchanged.
+fn dirty_bodies(world: &mut PhysicsContext) -> Vec<RigidBodyHandle> {
+    let awake: Vec<RigidBodyHandle> = world.islands.active_dynamic_bodies().iter()
+        .chain(world.islands.active_kinematic_bodies())
+        .copied()
+        .collect();
+    let damaged = world.pending_damage.keys().filter_map(|id| world.id_to_body.get(id));
+    let grounded = world.characters.iter()
+        .filter(|(_, c)| c.grounded != c.persisted_grounded)
+        .filter_map(|(id, _)| world.id_to_body.get(id));
+
+    let mut seen = HashSet::new();
+    let dirty = awake.iter().chain(&world.awake_bodies).chain(damaged).chain(grounded)
+        .copied()
+        .filter(|h| seen.insert(*h))
+        .collect();
+    world.awake_bodies = awake;
+    dirty
+}
+
+fn apply_database_updates(ctx: &ReducerContext, world: &mut PhysicsContext) -> WriteStats {
     // collect all changed physics_body rows in one batch
-    let mut updates = Vec::with_capacity(world.bodies.len());
+    let dirty = dirty_bodies(world);
+    let mut updates = Vec::with_capacity(dirty.len());
 
-    for (handle, body) in world.bodies.iter() {
-        // skip static/fixed bodies
+    for handle in dirty {
+        // skip static/fixed bodies and bodies despawned since they were last awake
+        let Some(body) = world.bodies.get(handle) else { continue };
         if body.is_fixed() {
             continue;
         }
@@ -96,14 +326,28 @@ fn apply_database_updates(ctx: &ReducerContext, world: &mut PhysicsContext) {
         let transform_changed = world
             .last_transforms
             .get(&handle)
-            .map_or(true, |(old_pos, old_rot)| *old_pos != pos || *old_rot != rot);
+            .is_none_or(|(old_pos, old_rot)| transform_exceeds_epsilon(old_pos, old_rot, &pos, &rot));
 
-        // pull out accumulated damage (0 if none)
+        // did a character's grounded flag flip since it was last written?
         let entity_id = unpack_id(body.user_data);
+        let character = world.characters.get_mut(&entity_id);
+        let grounded_changed = character.as_ref().is_some_and(|c| c.grounded != c.persisted_grounded);
+
+        // did the velocity change by more than the jitter threshold?
+        let linvel = *body.linvel();
+        let angvel = *body.angvel();
+        let velocity_changed = world
+            .last_velocities
+            .get(&handle)
+            .is_none_or(|(old_lin, old_ang)| {
+                velocity_exceeds_epsilon(old_lin, &linvel) || velocity_exceeds_epsilon(old_ang, &angvel)
+            });
+
+        // pull out accumulated damage (0 if none)
         let dmg = world.pending_damage.remove(&entity_id).unwrap_or(0);
 
-        // if nothing changed (neither movement nor damage), skip
-        if !transform_changed && dmg == 0 {
+        // if nothing changed (neither movement, velocity nor damage), skip
+        if !transform_changed && !velocity_changed && !grounded_changed && dmg == 0 {
             continue;
         }
 
@@ -111,7 +355,7 @@ fn apply_database_updates(ctx: &ReducerContext, world: &mut PhysicsContext) {
         if let Some(mut row) = ctx.db.physics_body().entity_id().find(entity_id) {
             // update position/rotation/chunk if moved
             if transform_changed {
-                let (chunk_x, chunk_y) = calculate_chunk_pair(pos.x, pos.y);
+                let (chunk_x, chunk_y) = chunk_of(&pos);
                 row.pos_x = pos.x;
                 row.pos_y = pos.y;
                 row.pos_z = pos.z;
@@ -125,6 +369,22 @@ fn apply_database_updates(ctx: &ReducerContext, world: &mut PhysicsContext) {
                 world.last_transforms.insert(handle, (pos, rot));
             }
 
+            // update velocities for client extrapolation and world restore
+            if velocity_changed {
+                row.vel_x = linvel.x;
+                row.vel_y = linvel.y;
+                row.vel_z = linvel.z;
+                row.ang_vel_x = angvel.x;
+                row.ang_vel_y = angvel.y;
+                row.ang_vel_z = angvel.z;
+                world.last_velocities.insert(handle, (linvel, angvel));
+            }
+
+            if let Some(character) = character {
+                row.grounded = character.grounded;
+                character.persisted_grounded = character.grounded;
+            }
+
             // apply damage if any
             if dmg > 0 {
                 row.health = row.health.saturating_sub(dmg);
@@ -135,13 +395,31 @@ fn apply_database_updates(ctx: &ReducerContext, world: &mut PhysicsContext) {
     }
 
     // write all changes in one go
-    if !updates.is_empty() {
-        for row in updates {
-           ctx.db.physics_body().entity_id().update(row);
-       }
-
+    let written = updates.len() as u32;
+    for row in updates {
+        ctx.db.physics_body().entity_id().update(row);
     }
 
     // clear the pending damage map for the next tick
     world.pending_damage.clear();
-}
\ No newline at end of file
+
+    let movable = world.bodies.iter().filter(|(_, b)| !b.is_fixed()).count() as u32;
+    WriteStats { written, skipped: movable.saturating_sub(written) }
+}
+
+/// True if the translation moved more than POSITION_EPSILON on any axis or the rotation turned more than ROTATION_EPSILON
+#[inline]
+fn transform_exceeds_epsilon(
+    old_pos: &Vector<Real>,
+    old_rot: &Rotation<Real>,
+    pos: &Vector<Real>,
+    rot: &Rotation<Real>,
+) -> bool {
+    (old_pos - pos).amax() > POSITION_EPSILON || old_rot.angle_to(rot) > ROTATION_EPSILON
+}
+
+/// True if any component differs by more than VELOCITY_EPSILON
+#[inline]
+fn velocity_exceeds_epsilon(old: &Vector<Real>, new: &Vector<Real>) -> bool {
+    (old - new).amax() > VELOCITY_EPSILON
+}
@@ -0,0 +1,118 @@
+//! Impulses, forces and velocities for dynamic bodies, addressed by physics entity ID
+use rapier3d::prelude::*;
+use spacetimedb::{reducer, ReducerContext};
+use crate::tables::physics_body::physics_body;
+use crate::reducers::auth::{check_player_limit, require_admin, PLAYER_MAX_ANGULAR_SPEED, PLAYER_MAX_FORCE, PLAYER_MAX_IMPULSE, PLAYER_MAX_SPEED};
+use crate::physics::{world_for_region, PhysicsContext, PHYSICS_CONTEXTS};
+
+/// Run `f` on the dynamic body registered under `entity_id` (projectiles are dynamic too)
+fn with_dynamic_body<R>(
+    world: &mut PhysicsContext,
+    entity_id: u32,
+    f: impl FnOnce(&mut RigidBody) -> R,
+) -> Result<R, String> {
+    let handle = *world.id_to_body.get(&entity_id).ok_or("Body not found in physics world")?;
+    let body = world.bodies.get_mut(handle).ok_or("Body not found in physics world")?;
+    if !body.is_dynamic() {
+        return Err("Only dynamic bodies can be pushed".into());
+    }
+    Ok(f(body))
+}
+
+/// Apply an instantaneous linear impulse at the center of mass
+pub fn apply_impulse_to(world: &mut PhysicsContext, entity_id: u32, impulse: Vector<Real>) -> Result<(), String> {
+    with_dynamic_body(world, entity_id, |b| b.apply_impulse(impulse, true))
+}
+
+/// Apply an instantaneous angular impulse
+pub fn apply_torque_impulse_to(world: &mut PhysicsContext, entity_id: u32, torque_impulse: Vector<Real>) -> Result<(), String> {
+    with_dynamic_body(world, entity_id, |b| b.apply_torque_impulse(torque_impulse, true))
+}
+
+/// Add a force acting for the next physics tick (cleared after it was stepped)
+pub fn add_force_to(world: &mut PhysicsContext, entity_id: u32, force: Vector<Real>) -> Result<(), String> {
+    with_dynamic_body(world, entity_id, |b| b.add_force(force, true))?;
+    let handle = world.id_to_body[&entity_id];
+    world.forced_bodies.push(handle);
+    Ok(())
+}
+
+/// Overwrite linear and angular velocity
+pub fn set_velocity_of(world: &mut PhysicsContext, entity_id: u32, linvel: Vector<Real>, angvel: Vector<Real>) -> Result<(), String> {
+    with_dynamic_body(world, entity_id, |b| {
+        b.set_linvel(linvel, true);
+        b.set_angvel(angvel, true);
+    })
+}
+
+/// Clear forces added through `add_force_to` once they acted for a tick
+pub(crate) fn reset_tick_forces(world: &mut PhysicsContext) {
+    for handle in world.forced_bodies.drain(..) {
+        if let Some(body) = world.bodies.get_mut(handle) {
+            body.reset_forces(false);
+        }
+    }
+}
+
+/// Resolve the caller's world for an entity, enforcing ownership (admins may move anything)
+fn with_owned_world<R>(
+    ctx: &ReducerContext,
+    entity_id: u32,
+    f: impl FnOnce(&mut PhysicsContext) -> Result<R, String>,
+) -> Result<R, String> {
+    let row = ctx.db.physics_body().entity_id().find(entity_id).ok_or("Body not found")?;
+    if row.owner_id != ctx.sender {
+        require_admin(ctx).map_err(|_| "You don't own this body".to_string())?;
+    }
+    let mut map = PHYSICS_CONTEXTS.lock().unwrap();
+    f(world_for_region(ctx, &mut map, row.region))
+}
+
+fn finite(values: &[f32]) -> Result<(), String> {
+    if values.iter().all(|v| v.is_finite()) { Ok(()) } else { Err("Non-finite vector".into()) }
+}
+
+#[reducer]
+/// Apply a linear impulse to a dynamic body you own
+pub fn apply_impulse(ctx: &ReducerContext, entity_id: u32, x: f32, y: f32, z: f32) -> Result<(), String> {
+    finite(&[x, y, z])?;
+    check_player_limit(ctx, "Impulse", [x, y, z], PLAYER_MAX_IMPULSE)?;
+    with_owned_world(ctx, entity_id, |w| apply_impulse_to(w, entity_id, vector![x, y, z]))
+}
+
+#[reducer]
+/// Apply an angular impulse to a dynamic body you own
+pub fn apply_torque_impulse(ctx: &ReducerContext, entity_id: u32, x: f32, y: f32, z: f32) -> Result<(), String> {
+    finite(&[x, y, z])?;
+    check_player_limit(ctx, "Angular impulse", [x, y, z], PLAYER_MAX_IMPULSE)?;
+    with_owned_world(ctx, entity_id, |w| apply_torque_impulse_to(w, entity_id, vector![x, y, z]))
+}
+
+#[reducer]
+/// Push a dynamic body you own with a force for the next physics tick
+pub fn apply_force(ctx: &ReducerContext, entity_id: u32, x: f32, y: f32, z: f32) -> Result<(), String> {
+    finite(&[x, y, z])?;
+    check_player_limit(ctx, "Force", [x, y, z], PLAYER_MAX_FORCE)?;
+    with_owned_world(ctx, entity_id, |w| add_force_to(w, entity_id, vector![x, y, z]))
+}
+
+#[reducer]
+#[allow(clippy::too_many_arguments)]
+/// Set the linear and angular velocity of a dynamic body you own
+pub fn set_body_velocity(
+    ctx: &ReducerContext,
+    entity_id: u32,
+    vel_x: f32,
+    vel_y: f32,
+    vel_z: f32,
+    ang_vel_x: f32,
+    ang_vel_y: f32,
+    ang_vel_z: f32,
+) -> Result<(), String> {
+    finite(&[vel_x, vel_y, vel_z, ang_vel_x, ang_vel_y, ang_vel_z])?;
+    check_player_limit(ctx, "Speed", [vel_x, vel_y, vel_z], PLAYER_MAX_SPEED)?;
+    check_player_limit(ctx, "Angular speed", [ang_vel_x, ang_vel_y, ang_vel_z], PLAYER_MAX_ANGULAR_SPEED)?;
+    with_owned_world(ctx, entity_id, |w| {
+        set_velocity_of(w, entity_id, vector![vel_x, vel_y, vel_z], vector![ang_vel_x, ang_vel_y, ang_vel_z])
+    })
+}
@@ -1,16 +1,28 @@
 use rapier3d::prelude::*;
-// Bounded channels for back-pressure - Using crossbeam::channel::bounded to limit event processing
-use crossbeam::channel::bounded;
-//use crossbeam::channel::unbounded;
+// Unbounded channels so no event is ever lost; back-pressure comes from the per-tick event budget
+use crossbeam::channel::unbounded;
 use spacetimedb::reducer;
-use spacetimedb::ReducerContext;
+use spacetimedb::{ReducerContext, Table};
+use crate::tables::physics_tick_stats::{physics_tick_stats, PhysicsTickStats};
 use crate::tables::scheduling::PhysicsTickSchedule;
-use crate::physics::contact_tracker::{handle_event, process_contacts};
-use crate::physics::{drain_collision_events, apply_database_updates};
+use crate::physics::contact_tracker::{handle_event, handle_force_events, process_contacts};
+use crate::physics::handoff::migrate_bodies;
+use crate::physics::character::{consume_inputs, move_characters};
+use crate::physics::motion::reset_tick_forces;
+use crate::physics::projectile::expire_projectiles;
+use crate::physics::hooks::expire_invulnerability;
+use crate::physics::rewind::record_history;
+use crate::physics::spawn::flush_pending_despawns;
+use crate::physics::dormancy::{ready_to_sleep, sleep_region};
+use crate::physics::{take_collision_events, apply_database_updates, WriteStats, step_world, loaded_world, load_region_config};
 
-/// Maximum number of collision events to process per tick
+/// Default number of collision events to process per tick (see `RegionConfig::max_collision_events`);
+/// the rest are deferred to the next tick, never dropped
 pub const MAX_COLLISION_EVENTS: usize = 100;
 
+/// Maximum fixed sub-steps per tick; any backlog beyond this is dropped with a warning
+pub const MAX_SUBSTEPS_PER_TICK: u32 = 12;
+
 pub use crate::physics::PHYSICS_CONTEXTS;
 pub use crate::physics::PhysicsContext;
 /// Scheduled reducer for stepping physics each tick
@@ -22,44 +34,59 @@ pub fn physics_tick(ctx: &ReducerContext, schedule: PhysicsTickSchedule) -> Resu
     }
     
     let region = schedule.region;
+    let config = load_region_config(ctx, region);
     
     // lock and get or init context
     let mut map = PHYSICS_CONTEXTS.lock().unwrap();
-    // construct a PhysicsContext for this region if it doesn't exist
-    let world = map.entry(region)
-                                        .or_default();
-                           
-
-    // Use bounded channels to prevent event overflow - will drop events if channel fills up
-    let (collision_tx, collision_rx) = bounded(MAX_COLLISION_EVENTS);
-    let (contact_tx, _) = bounded(MAX_COLLISION_EVENTS);
+    // construct a PhysicsContext for this region if it doesn't exist, restoring persisted bodies
+    let world = loaded_world(ctx, &mut map, region);
+    // pick up config changes made since the last tick
+    world.apply_config(&config);
+    let event_budget = config.max_collision_events as usize;
+
+    // Collect every event of every sub-step; budgets are applied when processing
+    let (collision_tx, collision_rx) = unbounded();
+    let (contact_tx, contact_rx) = unbounded();
     let collector = ChannelEventCollector::new(collision_tx, contact_tx);
 
-    // Step physics simulation with event handler
-    world.pipeline.step(
-        &world.gravity,
-        &world.integration_parameters,
-        &mut world.islands,
-        &mut world.broad_phase,
-        &mut world.narrow_phase,
-        &mut world.bodies,
-        &mut world.colliders,
-        &mut world.impulse_joints,
-        &mut world.multibody_joints,
-        &mut world.ccd_solver,
-        None,
-        &(),
-        &collector,
-    );
+    // Accumulate the real time since the previous tick (first tick assumes the nominal interval)
+    let now = ctx.timestamp;
+    let elapsed_micros = world.last_tick_at
+        .map(|prev| (now.to_micros_since_unix_epoch() - prev.to_micros_since_unix_epoch()).max(0))
+        .unwrap_or(config.tick_interval_micros);
+    world.last_tick_at = Some(now);
+    world.accumulator += elapsed_micros as Real / 1_000_000.0;
+
+    // Resolve character movement for the time this tick is about to simulate
+    let dt = world.integration_parameters.dt;
+    let (substeps, dropped) = take_substeps(&mut world.accumulator, dt);
+    let tick_dt = substeps as Real * dt;
+    consume_inputs(ctx, world, tick_dt);
+    move_characters(world, tick_dt);
+
+    // Step physics in fixed sub-steps; the collector gathers events from every sub-step
+    for _ in 0..substeps {
+        step_world(world, &collector);
+    }
+    if substeps > 0 {
+        reset_tick_forces(world);
+    }
+    if dropped > 0.0 {
+        log::warn!("Physics tick overrun in region {}: hit {} sub-steps, dropping {:.3}s of simulation",
+            region, MAX_SUBSTEPS_PER_TICK, dropped);
+    }
 
     world.query_pipeline.update(&world.colliders);
+    record_history(world, now);
 
 
-    // Drain collision events and warn if at capacity
-    let events = drain_collision_events(&collision_rx);
-    if events.len() == MAX_COLLISION_EVENTS {
-        log::warn!("Reached maximum collision events ({}), some may have been dropped", MAX_COLLISION_EVENTS);
+    // Queue this tick's events behind any backlog and take at most the budget, oldest first
+    let events = take_collision_events(&collision_rx, &mut world.pending_events, event_budget);
+    let deferred = world.pending_events.len();
+    if deferred > 0 {
+        log::warn!("Collision event budget ({}) reached in region {}, deferring {} events", event_budget, region, deferred);
     }
+    let force_events: Vec<ContactForceEvent> = contact_rx.try_iter().collect();
 
     // Process contact-duration events
     // Process Start, Continue, and End contacts and handle events
@@ -67,8 +94,25 @@ pub fn physics_tick(ctx: &ReducerContext, schedule: PhysicsTickSchedule) -> Resu
     for contact in contacts {
         handle_event(ctx, world, contact);
     }
+    // impacts above a collider's contact-force threshold
+    handle_force_events(ctx, world, &force_events, dt);
+
+    // retire projectiles past their lifetime or range, then despawn everything queued this tick
+    expire_projectiles(ctx, world);
+    expire_invulnerability(world, ctx.timestamp);
+    flush_pending_despawns(ctx, world);
 
-    apply_database_updates(ctx, world);
+    let writes = apply_database_updates(ctx, world);
+    record_tick_stats(ctx, region, events.len(), deferred, writes);
+
+    // hand off bodies that crossed into another region's chunk range
+    migrate_bodies(ctx, &mut map, region);
+
+    // regions without players stop ticking until something wakes them
+    if map.get_mut(&region).is_some_and(|world| ready_to_sleep(ctx, world, region)) {
+        sleep_region(ctx, &mut map, region);
+        return Ok(());
+    }
     
     // Schedule the next tick (self-scheduling for continuous physics)
     if let Err(e) = crate::reducers::lifecycle::schedule_physics_tick(ctx, region, Some(schedule.scheduled_id)) {
@@ -76,4 +120,41 @@ pub fn physics_tick(ctx: &ReducerContext, schedule: PhysicsTickSchedule) -> Resu
     }
     
     Ok(())
-}
\ No newline at end of file
+}
+/// Take whole fixed steps of `dt` out of the accumulator, at most `MAX_SUBSTEPS_PER_TICK`.
+/// Returns the step count and the simulated time dropped on overrun; only the sub-step
+/// remainder is kept then, so the world doesn't spiral further behind.
+pub(crate) fn take_substeps(accumulator: &mut Real, dt: Real) -> (u32, Real) {
+    let mut substeps = 0;
+    while *accumulator >= dt && substeps < MAX_SUBSTEPS_PER_TICK {
+        *accumulator -= dt;
+        substeps += 1;
+    }
+    let mut dropped = 0.0;
+    if *accumulator >= dt {
+        dropped = *accumulator - *accumulator % dt;
+        *accumulator %= dt;
+    }
+    (substeps, dropped)
+}
+
+/// Update the region's `physics_tick_stats` row
+fn record_tick_stats(ctx: &ReducerContext, region: u32, processed: usize, deferred: usize, writes: WriteStats) {
+    let prev = ctx.db.physics_tick_stats().region().find(region);
+    let stats = PhysicsTickStats {
+        region,
+        ticks: prev.as_ref().map_or(0, |s| s.ticks) + 1,
+        events_processed: processed as u32,
+        events_deferred: deferred as u32,
+        total_deferred: prev.as_ref().map_or(0, |s| s.total_deferred) + deferred as u64,
+        rows_written: writes.written,
+        rows_skipped: writes.skipped,
+        updated_at: ctx.timestamp,
+        dormant_since: None,
+    };
+    if prev.is_some() {
+        ctx.db.physics_tick_stats().region().update(stats);
+    } else {
+        ctx.db.physics_tick_stats().insert(stats);
+    }
+}
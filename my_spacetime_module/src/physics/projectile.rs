@@ -0,0 +1,123 @@
+//! Projectile lifecycle: time-to-live, hit budget, max distance and impact behaviour
+use rapier3d::prelude::*;
+use spacetimedb::{reducer, ReducerContext, Table};
+use crate::tables::physics_body::PhysicsBody;
+use crate::tables::projectile::{projectile, ImpactBehavior, Projectile, ProjectileSpec};
+use crate::spacetime_common::collision::PROJECTILE_BODY_TYPE;
+use crate::physics::spawn::{new_body_row, spawn_body};
+use crate::physics::PhysicsContext;
+use crate::reducers::auth::{can_spawn, check_player_spawn};
+use crate::tables::despawn_event::DespawnReason;
+use crate::spacetime_common::shape::{ColliderShape, ColliderSpec};
+
+/// Limits for projectiles spawned through `spawn_rigid_body` without a spec
+pub const DEFAULT_PROJECTILE_SPEC: ProjectileSpec = ProjectileSpec {
+    max_lifetime_ms: 5_000,
+    max_hits: 1,
+    max_distance: 200.0,
+    on_impact: ImpactBehavior::Despawn,
+};
+
+/// Start tracking a freshly spawned projectile body
+pub(crate) fn register_projectile(ctx: &ReducerContext, row: &PhysicsBody, spec: ProjectileSpec) {
+    ctx.db.projectile().insert(Projectile {
+        entity_id: row.entity_id,
+        owner_id: row.owner_id,
+        spawned_at: ctx.timestamp,
+        origin_x: row.pos_x,
+        origin_y: row.pos_y,
+        origin_z: row.pos_z,
+        spec,
+        hits: 0,
+    });
+}
+
+/// Apply the impact behaviour to a projectile's collider (on spawn and after hydration)
+pub(crate) fn configure_collider(world: &mut PhysicsContext, entity_id: u32, on_impact: ImpactBehavior) {
+    let Some(&handle) = world.id_to_body.get(&entity_id) else { return };
+    let Some(body) = world.bodies.get(handle) else { return };
+    for &col_handle in body.colliders() {
+        if let Some(col) = world.colliders.get_mut(col_handle) {
+            match on_impact {
+                ImpactBehavior::Despawn => {}
+                ImpactBehavior::Pierce => col.set_sensor(true),
+                ImpactBehavior::Bounce => {
+                    col.set_restitution(1.0);
+                    col.set_restitution_combine_rule(CoefficientCombineRule::Max);
+                }
+            }
+        }
+    }
+}
+
+/// Re-apply impact behaviour to projectile colliders restored by hydration
+pub(crate) fn restore_colliders(ctx: &ReducerContext, world: &mut PhysicsContext) {
+    for p in ctx.db.projectile().iter() {
+        configure_collider(world, p.entity_id, p.spec.on_impact);
+    }
+}
+
+/// A projectile is used up by its first impact unless it pierces or bounces, else by its hit budget
+pub(crate) fn is_spent(spec: &ProjectileSpec, hits: u8) -> bool {
+    spec.on_impact == ImpactBehavior::Despawn || hits >= spec.max_hits
+}
+
+/// Past its lifetime at `now_micros`, or further than its range from where it was fired;
+/// returns the age (ms) and distance travelled when expired
+pub(crate) fn expiry(p: &Projectile, now_micros: i64, pos: &Vector<Real>) -> Option<(i64, Real)> {
+    let age_ms = (now_micros - p.spawned_at.to_micros_since_unix_epoch()) / 1000;
+    let travelled = (pos - vector![p.origin_x, p.origin_y, p.origin_z]).norm();
+    (age_ms >= p.spec.max_lifetime_ms as i64 || travelled >= p.spec.max_distance).then_some((age_ms, travelled))
+}
+
+/// Count a hit for a projectile and queue its despawn if its behaviour or hit budget says so
+pub(crate) fn on_projectile_hit(ctx: &ReducerContext, world: &mut PhysicsContext, entity_id: u32) {
+    let Some(mut p) = ctx.db.projectile().entity_id().find(entity_id) else { return };
+    p.hits = p.hits.saturating_add(1);
+    if is_spent(&p.spec, p.hits) {
+        log::debug!("Projectile {} spent after {} hits", entity_id, p.hits);
+        world.pending_despawns.push((entity_id, DespawnReason::ProjectileSpent));
+    }
+    ctx.db.projectile().entity_id().update(p);
+}
+
+/// Queue despawns for projectiles of this world that outlived their lifetime or range
+pub(crate) fn expire_projectiles(ctx: &ReducerContext, world: &mut PhysicsContext) {
+    let now = ctx.timestamp.to_micros_since_unix_epoch();
+    for p in ctx.db.projectile().iter() {
+        let Some(&handle) = world.id_to_body.get(&p.entity_id) else { continue };
+        let Some(body) = world.bodies.get(handle) else { continue };
+
+        if let Some((age_ms, travelled)) = expiry(&p, now, body.translation()) {
+            log::debug!("Projectile {} expired (age {}ms, distance {:.1})", p.entity_id, age_ms, travelled);
+            world.pending_despawns.push((p.entity_id, DespawnReason::ProjectileExpired));
+        }
+    }
+}
+
+#[reducer]
+#[allow(clippy::too_many_arguments)]
+/// Fire a spherical projectile owned by the caller with the given lifecycle limits
+pub fn spawn_projectile(
+    ctx: &ReducerContext,
+    region: u32,
+    x: f32,
+    y: f32,
+    z: f32,
+    vel_x: f32,
+    vel_y: f32,
+    vel_z: f32,
+    radius: f32,
+    spec: ProjectileSpec,
+) -> Result<(), String> {
+    can_spawn(ctx, PROJECTILE_BODY_TYPE)?;
+    let positive = |v: f32| v.is_finite() && v > 0.0;
+    if !positive(radius) || !positive(spec.max_distance) || spec.max_hits == 0 || spec.max_lifetime_ms == 0 {
+        return Err("Invalid projectile parameters".into());
+    }
+    let mut row = new_body_row(ctx.sender, region, x, y, z, vec![ColliderSpec::new(ColliderShape::Sphere(radius))], PROJECTILE_BODY_TYPE);
+    (row.vel_x, row.vel_y, row.vel_z) = (vel_x, vel_y, vel_z);
+    check_player_spawn(ctx, &row)?;
+    spawn_body(ctx, row, Some(spec))?;
+    Ok(())
+}
@@ -0,0 +1,294 @@
+//! Scene queries on a region's world: raycast, shape-cast and overlap.
+//!
+//! The plain functions take a `PhysicsContext` so combat, pickup and AI code can share
+//! them inside their own lock; the reducers expose them to clients via `query_hit` rows.
+use crate::physics::rapier_common::*;
+use rapier3d::prelude::*;
+use rapier3d::parry::query::ShapeCastOptions;
+use spacetimedb::{reducer, ReducerContext, Table, Timestamp};
+use crate::physics::{existing_world, PhysicsContext, PHYSICS_CONTEXTS};
+use crate::tables::player::player;
+use crate::tables::query_hit::{query_hit, QueryFilterSpec, QueryHit, QueryShape};
+
+/// How long `query_hit` rows stay around for the client to read them
+pub const QUERY_HIT_TTL_MICROS: i64 = 1_000_000;
+/// Largest sphere radius or box half extent a query reducer accepts
+pub const MAX_QUERY_EXTENT: f32 = 50.0;
+/// Longest ray or sweep a query reducer accepts
+pub const MAX_QUERY_DISTANCE: f32 = 500.0;
+/// Most `query_hit` rows written for one query; further hits are dropped
+pub const MAX_QUERY_HITS: usize = 64;
+
+/// Filter applied to every scene query
+#[derive(Clone, Copy, Debug)]
+pub struct QueryOptions {
+    pub groups: InteractionGroups,
+    /// Entity whose colliders are ignored (usually the caster)
+    pub exclude_entity: Option<u32>,
+    pub exclude_sensors: bool,
+}
+
+impl Default for QueryOptions {
+    fn default() -> Self {
+        QueryOptions { groups: InteractionGroups::all(), exclude_entity: None, exclude_sensors: false }
+    }
+}
+
+/// A single query result in world space
+#[derive(Clone, Copy, Debug)]
+pub struct SceneHit {
+    pub entity_id: u32,
+    pub point: Point<Real>,
+    /// Outward surface normal at `point` (zero for overlaps where it's undefined)
+    pub normal: Vector<Real>,
+    /// Distance travelled along the ray or sweep before the hit
+    pub toi: Real,
+}
+
+/// Build Rapier's filter from our options and hand it to `f`
+fn with_filter<R>(world: &PhysicsContext, opts: &QueryOptions, f: impl FnOnce(QueryFilter) -> R) -> R {
+    let mut filter = QueryFilter::default().groups(opts.groups);
+    if opts.exclude_sensors {
+        filter = filter.exclude_sensors();
+    }
+    if let Some(&handle) = opts.exclude_entity.and_then(|id| world.id_to_body.get(&id)) {
+        filter = filter.exclude_rigid_body(handle);
+    }
+    f(filter)
+}
+
+fn entity_of(world: &PhysicsContext, handle: ColliderHandle) -> Option<u32> {
+    world.colliders.get(handle).map(|c| unpack_id(c.user_data))
+}
+
+/// Closest hit along a ray; `dir` need not be normalized, `max_dist` is in world units
+pub fn raycast(world: &PhysicsContext, origin: Point<Real>, dir: Vector<Real>, max_dist: Real, opts: &QueryOptions) -> Option<SceneHit> {
+    let dir = dir.try_normalize(Real::EPSILON)?;
+    let ray = Ray::new(origin, dir);
+    let (handle, hit) = with_filter(world, opts, |filter| {
+        world.query_pipeline.cast_ray_and_get_normal(&world.bodies, &world.colliders, &ray, max_dist, true, filter)
+    })?;
+    Some(SceneHit {
+        entity_id: entity_of(world, handle)?,
+        point: ray.point_at(hit.time_of_impact),
+        normal: hit.normal,
+        toi: hit.time_of_impact,
+    })
+}
+
+/// First collider touched when sweeping `shape` from `origin` along `dir` for `max_dist`
+pub fn shape_cast(
+    world: &PhysicsContext,
+    shape: &dyn Shape,
+    origin: Point<Real>,
+    dir: Vector<Real>,
+    max_dist: Real,
+    opts: &QueryOptions,
+) -> Option<SceneHit> {
+    let dir = dir.try_normalize(Real::EPSILON)?;
+    let pos = Isometry::translation(origin.x, origin.y, origin.z);
+    let options = ShapeCastOptions {
+        compute_impact_geometry_on_penetration: true,
+        ..ShapeCastOptions::with_max_time_of_impact(max_dist)
+    };
+    let (handle, hit) = with_filter(world, opts, |filter| {
+        world.query_pipeline.cast_shape(&world.bodies, &world.colliders, &pos, &dir, shape, options, filter)
+    })?;
+    // witness2/normal2 are local to the collider that was hit
+    let col_pos = world.colliders.get(handle)?.position();
+    Some(SceneHit {
+        entity_id: entity_of(world, handle)?,
+        point: col_pos * hit.witness2,
+        normal: col_pos * hit.normal2.into_inner(),
+        toi: hit.time_of_impact,
+    })
+}
+
+/// Entities whose colliders contain `point`
+pub fn overlap_point(world: &PhysicsContext, point: Point<Real>, opts: &QueryOptions) -> Vec<SceneHit> {
+    let mut handles = Vec::new();
+    with_filter(world, opts, |filter| {
+        world.query_pipeline.intersections_with_point(&world.bodies, &world.colliders, &point, filter, |h| {
+            handles.push(h);
+            true
+        })
+    });
+    collect_overlaps(world, point, handles)
+}
+
+/// Entities whose colliders intersect `shape` placed at `center`
+pub fn overlap_shape(world: &PhysicsContext, shape: &dyn Shape, center: Point<Real>, opts: &QueryOptions) -> Vec<SceneHit> {
+    let pos = Isometry::translation(center.x, center.y, center.z);
+    let mut handles = Vec::new();
+    with_filter(world, opts, |filter| {
+        world.query_pipeline.intersections_with_shape(&world.bodies, &world.colliders, &pos, shape, filter, |h| {
+            handles.push(h);
+            true
+        })
+    });
+    collect_overlaps(world, center, handles)
+}
+
+/// True if nothing solid (other than `target` itself) blocks the segment `from` → `to`
+pub fn line_of_sight(world: &PhysicsContext, from: Point<Real>, to: Point<Real>, target: u32, opts: &QueryOptions) -> bool {
+    let dist = (to - from).norm();
+    if dist <= Real::EPSILON {
+        return true;
+    }
+    let opts = QueryOptions { exclude_sensors: true, ..*opts };
+    match raycast(world, from, to - from, dist, &opts) {
+        Some(hit) => hit.entity_id == target || hit.toi >= dist - 1.0e-3,
+        None => true,
+    }
+}
+
+/// One hit per entity, using the collider point closest to `center`
+fn collect_overlaps(world: &PhysicsContext, center: Point<Real>, handles: Vec<ColliderHandle>) -> Vec<SceneHit> {
+    let mut hits: Vec<SceneHit> = Vec::new();
+    for handle in handles {
+        let Some(col) = world.colliders.get(handle) else { continue };
+        let entity_id = unpack_id(col.user_data);
+        if hits.iter().any(|h| h.entity_id == entity_id) {
+            continue;
+        }
+        let proj = col.shape().project_point(col.position(), &center, true);
+        hits.push(SceneHit { entity_id, point: proj.point, normal: Vector::zeros(), toi: 0.0 });
+    }
+    hits
+}
+
+// ———————————————— Reducers ————————————————
+
+/// The caller's player body, if they have one
+pub fn caster_entity(ctx: &ReducerContext) -> Option<u32> {
+    ctx.db.player().player_id().find(ctx.sender).map(|p| p.phy_entity_id)
+}
+
+fn options_from(ctx: &ReducerContext, spec: &QueryFilterSpec) -> QueryOptions {
+    QueryOptions {
+        groups: InteractionGroups::new(Group::from_bits_truncate(spec.memberships), Group::from_bits_truncate(spec.filter)),
+        exclude_entity: if spec.exclude_caster { caster_entity(ctx) } else { None },
+        exclude_sensors: spec.exclude_sensors,
+    }
+}
+
+pub(crate) fn to_shape(shape: QueryShape) -> Result<SharedShape, String> {
+    let valid = |v: f32| v.is_finite() && v > 0.0 && v <= MAX_QUERY_EXTENT;
+    match shape {
+        QueryShape::Sphere(r) if valid(r) => Ok(SharedShape::ball(r)),
+        QueryShape::Cuboid(e) if valid(e.hx) && valid(e.hy) && valid(e.hz) => Ok(SharedShape::cuboid(e.hx, e.hy, e.hz)),
+        _ => Err(format!("Query shape dimensions must be positive and at most {}", MAX_QUERY_EXTENT)),
+    }
+}
+
+pub(crate) fn check_cast(values: &[f32], max_dist: f32) -> Result<(), String> {
+    if !values.iter().all(|v| v.is_finite()) || !max_dist.is_finite() || max_dist <= 0.0 {
+        return Err("Non-finite query parameters or non-positive distance".into());
+    }
+    if max_dist > MAX_QUERY_DISTANCE {
+        return Err(format!("Query distance above {}", MAX_QUERY_DISTANCE));
+    }
+    Ok(())
+}
+
+/// Run `f` against the region's world (hydrating it if needed) and store up to
+/// `MAX_QUERY_HITS` hits; fails for regions that don't exist
+fn run_query(
+    ctx: &ReducerContext,
+    query_id: u32,
+    region: u32,
+    f: impl FnOnce(&PhysicsContext) -> Vec<SceneHit>,
+) -> Result<(), String> {
+    let hits = {
+        let mut map = PHYSICS_CONTEXTS.lock().unwrap();
+        f(existing_world(ctx, &mut map, region)?)
+    };
+    let expire_at = Timestamp::from_micros_since_unix_epoch(
+        ctx.timestamp.to_micros_since_unix_epoch() + QUERY_HIT_TTL_MICROS
+    );
+    for hit in hits.into_iter().take(MAX_QUERY_HITS) {
+        ctx.db.query_hit().insert(QueryHit {
+            hit_id: 0,
+            requester: ctx.sender,
+            query_id,
+            region,
+            entity_id: hit.entity_id,
+            point_x: hit.point.x,
+            point_y: hit.point.y,
+            point_z: hit.point.z,
+            normal_x: hit.normal.x,
+            normal_y: hit.normal.y,
+            normal_z: hit.normal.z,
+            toi: hit.toi,
+            expire_at,
+        });
+    }
+    Ok(())
+}
+
+#[reducer]
+#[allow(clippy::too_many_arguments)]
+/// Cast a ray in a region; the closest hit (if any) is written to `query_hit`
+pub fn raycast_query(
+    ctx: &ReducerContext,
+    query_id: u32,
+    region: u32,
+    origin_x: f32, origin_y: f32, origin_z: f32,
+    dir_x: f32, dir_y: f32, dir_z: f32,
+    max_dist: f32,
+    filter: QueryFilterSpec,
+) -> Result<(), String> {
+    check_cast(&[origin_x, origin_y, origin_z, dir_x, dir_y, dir_z], max_dist)?;
+    let opts = options_from(ctx, &filter);
+    run_query(ctx, query_id, region, |world| {
+        raycast(world, point![origin_x, origin_y, origin_z], vector![dir_x, dir_y, dir_z], max_dist, &opts)
+            .into_iter().collect()
+    })
+}
+
+#[reducer]
+#[allow(clippy::too_many_arguments)]
+/// Sweep a sphere or box through a region; the first hit (if any) is written to `query_hit`
+pub fn shape_cast_query(
+    ctx: &ReducerContext,
+    query_id: u32,
+    region: u32,
+    shape: QueryShape,
+    origin_x: f32, origin_y: f32, origin_z: f32,
+    dir_x: f32, dir_y: f32, dir_z: f32,
+    max_dist: f32,
+    filter: QueryFilterSpec,
+) -> Result<(), String> {
+    check_cast(&[origin_x, origin_y, origin_z, dir_x, dir_y, dir_z], max_dist)?;
+    let shape = to_shape(shape)?;
+    let opts = options_from(ctx, &filter);
+    run_query(ctx, query_id, region, |world| {
+        shape_cast(world, &*shape, point![origin_x, origin_y, origin_z], vector![dir_x, dir_y, dir_z], max_dist, &opts)
+            .into_iter().collect()
+    })
+}
+
+#[reducer]
+#[allow(clippy::too_many_arguments)]
+/// List the entities at a point, or inside a shape centred on it when `shape` is set
+pub fn overlap_query(
+    ctx: &ReducerContext,
+    query_id: u32,
+    region: u32,
+    shape: Option<QueryShape>,
+    x: f32,
+    y: f32,
+    z: f32,
+    filter: QueryFilterSpec,
+) -> Result<(), String> {
+    if ![x, y, z].iter().all(|v| v.is_finite()) {
+        return Err("Non-finite query position".into());
+    }
+    let shape = shape.map(to_shape).transpose()?;
+    let opts = options_from(ctx, &filter);
+    let center = point![x, y, z];
+    run_query(ctx, query_id, region, |world| match &shape {
+        Some(shape) => overlap_shape(world, &**shape, center, &opts),
+        None => overlap_point(world, center, &opts),
+    })
+}
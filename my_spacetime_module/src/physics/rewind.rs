@@ -0,0 +1,94 @@
+//! Lag compensation: per-tick collider transform history and scene queries against a past tick.
+//!
+//! Each physics tick records where every non-fixed collider was. `with_rewound` moves the
+//! colliders back to the state at the time a client saw (interpolated between the two nearest
+//! ticks), rebuilds the `QueryPipeline`, runs the queries and then restores the present.
+//! How far back a client may ask is bounded by `RegionConfig::max_rewind_micros`.
+use rapier3d::prelude::*;
+use spacetimedb::Timestamp;
+use std::collections::HashMap;
+use crate::physics::PhysicsContext;
+
+/// Default `RegionConfig::max_rewind_micros`: covers clients with up to ~250 ms of latency
+pub const MAX_REWIND_MICROS: i64 = 250_000;
+
+/// Upper bound an admin may set for `RegionConfig::max_rewind_micros` (history memory grows with it)
+pub const MAX_REWIND_LIMIT_MICROS: i64 = 1_000_000;
+
+/// Where every non-fixed collider was at the end of one physics tick
+pub struct TransformSnapshot {
+    pub at: Timestamp,
+    pub poses: Vec<(ColliderHandle, Isometry<Real>)>,
+}
+
+/// Record this tick's collider transforms and drop snapshots older than the rewind window
+pub(crate) fn record_history(world: &mut PhysicsContext, now: Timestamp) {
+    let poses = world.colliders.iter()
+        .filter(|(_, c)| c.parent().and_then(|b| world.bodies.get(b)).is_some_and(|b| !b.is_fixed()))
+        .map(|(h, c)| (h, *c.position()))
+        .collect();
+    world.history.push_back(TransformSnapshot { at: now, poses });
+
+    // keep one snapshot at or before the window start so its edge can still be interpolated
+    let oldest = now.to_micros_since_unix_epoch() - world.max_rewind_micros;
+    while world.history.get(1).is_some_and(|s| s.at.to_micros_since_unix_epoch() <= oldest) {
+        world.history.pop_front();
+    }
+}
+
+/// The time to rewind to for a client that saw the world at `view_at`, clamped to the
+/// region's rewind window; None means the present state should be used
+pub fn rewind_target(world: &PhysicsContext, now: Timestamp, view_at: Option<Timestamp>) -> Option<i64> {
+    let view = view_at?.to_micros_since_unix_epoch();
+    let now = now.to_micros_since_unix_epoch();
+    let latest = world.history.back()?.at.to_micros_since_unix_epoch();
+    let target = view.clamp(now - world.max_rewind_micros, now);
+    (target < latest).then_some(target)
+}
+
+/// Collider poses at `target` (micros), interpolated between the two snapshots around it
+fn poses_at(world: &PhysicsContext, target: i64) -> Vec<(ColliderHandle, Isometry<Real>)> {
+    let after = world.history.partition_point(|s| s.at.to_micros_since_unix_epoch() <= target);
+    let Some(before) = after.checked_sub(1).map(|i| &world.history[i]) else {
+        // older than anything recorded: the oldest snapshot is the best we have
+        return world.history.front().map_or_else(Vec::new, |s| s.poses.clone());
+    };
+    let Some(next) = world.history.get(after) else { return before.poses.clone() };
+
+    let (t0, t1) = (before.at.to_micros_since_unix_epoch(), next.at.to_micros_since_unix_epoch());
+    let frac = ((target - t0) as Real / (t1 - t0).max(1) as Real).clamp(0.0, 1.0);
+    let next_poses: HashMap<ColliderHandle, Isometry<Real>> = next.poses.iter().copied().collect();
+    before.poses.iter()
+        .map(|&(h, pose)| (h, next_poses.get(&h).map_or(pose, |n| pose.lerp_slerp(n, frac))))
+        .collect()
+}
+
+/// Run `f` against the world as a client saw it at `view_at` (see `rewind_target`).
+/// Colliders spawned since then are queried where they are now.
+pub fn with_rewound<R>(
+    world: &mut PhysicsContext,
+    now: Timestamp,
+    view_at: Option<Timestamp>,
+    f: impl FnOnce(&PhysicsContext) -> R,
+) -> R {
+    let Some(target) = rewind_target(world, now, view_at) else { return f(world) };
+
+    let mut present = Vec::new();
+    for (handle, pose) in poses_at(world, target) {
+        if let Some(col) = world.colliders.get_mut(handle) {
+            present.push((handle, *col.position()));
+            col.set_position(pose);
+        }
+    }
+    world.query_pipeline.update(&world.colliders);
+
+    let result = f(world);
+
+    for (handle, pose) in present {
+        if let Some(col) = world.colliders.get_mut(handle) {
+            col.set_position(pose);
+        }
+    }
+    world.query_pipeline.update(&world.colliders);
+    result
+}
@@ -5,15 +5,13 @@ use rapier3d::prelude::*;
 use spacetimedb::{reducer, ReducerContext, Timestamp, Table};
 use crate::tables::player_buffs::{player_buffs, PlayerBuff};
 use crate::tables::skill_cooldown::{skill_cooldown, SkillCooldown};
-use crate::physics::PHYSICS_CONTEXTS;
+use crate::physics::{PhysicsContext, PHYSICS_CONTEXTS};
 use crate::physics::contact_tracker::register_owner;
-use rapier3d::pipeline::QueryFilter;
+use crate::physics::queries::{caster_entity, raycast, QueryOptions};
+use crate::physics::rewind::with_rewound;
 use crate::tables::damage_event::{damage_event, DamageEvent};
 use crate::tables::physics_body::physics_body;
-use std::sync::atomic::{AtomicU64, Ordering};
-
-/// Global buff-ID generator
-static BUFF_ID: AtomicU64 = AtomicU64::new(1);
+use crate::spacetime_common::sequence::{next_id, Sequence};
 
 pub type SkillId = u8;
 pub type BuffType = u8;
@@ -89,6 +87,7 @@ trait SkillBehavior: Sync + Send + 'static { // Each implementor provides ID, ba
         dx: f32,
         dy: f32,
         dz: f32,
+        view_at: Option<Timestamp>,
     );
 }
 
@@ -102,13 +101,14 @@ struct SensorSkill {
 impl SkillBehavior for SensorSkill {
     fn id(&self) -> SkillId { self.id }
     fn base_ms(&self) -> u32 { 1000 }
-    fn activate(&self, ctx: &ReducerContext, x: f32, y: f32, z: f32, _: f32, _: f32, _: f32) {
+    fn activate(&self, ctx: &ReducerContext, x: f32, y: f32, z: f32, _: f32, _: f32, _: f32, _: Option<Timestamp>) {
+        let Some(region) = caster_region(ctx) else { return };
         let mut worlds = PHYSICS_CONTEXTS.lock().unwrap();
-        if let Some(col) = worlds.values_mut().flat_map(|w| w.colliders.get_mut(self.collider_handle)).next() {
+        if let Some(col) = worlds.get_mut(&region).and_then(|w| w.colliders.get_mut(self.collider_handle)) {
             col.set_position(Isometry::translation(x,y,z));
             col.set_collision_groups(self.active_groups);
+            register_owner(region, self.collider_handle, ctx.sender);
         }
-        register_owner(self.collider_handle, ctx.sender);
     }
 }
 
@@ -117,22 +117,29 @@ struct RaySkill { id: SkillId }
 impl SkillBehavior for RaySkill {
     fn id(&self) -> SkillId { self.id }
     fn base_ms(&self) -> u32 { 1000 }
-    fn activate(&self, ctx: &ReducerContext, x: f32, y: f32, z: f32, dx: f32, dy: f32, dz: f32) {
-        let ray = Ray::new(Point::new(x,y,z), Vector::new(dx,dy,dz));
-        for world in PHYSICS_CONTEXTS.lock().unwrap().values() {
-            if let Some((col_handle, _toi)) = world.query_pipeline.cast_ray(
-                &world.bodies, &world.colliders,
-                &ray, f32::MAX, true, QueryFilter::default()
-            ) {
-                let col = &world.colliders[col_handle];
-                let body = &world.bodies[col.parent().unwrap()];
-                let target = unpack_id(body.user_data);
-                apply_damage(ctx, self.id(), target, 1);
-            }
+    fn activate(&self, ctx: &ReducerContext, x: f32, y: f32, z: f32, dx: f32, dy: f32, dz: f32, view_at: Option<Timestamp>) {
+        let Some(region) = caster_region(ctx) else { return };
+        let opts = QueryOptions { exclude_entity: caster_entity(ctx), ..QueryOptions::default() };
+        // collect first: apply_damage takes the PHYSICS_CONTEXTS lock itself;
+        // the target is where the caster saw it (lag compensation)
+        let hit = PHYSICS_CONTEXTS.lock().unwrap().get_mut(&region).and_then(|world| {
+            with_rewound(world, ctx.timestamp, view_at, |w| {
+                raycast(w, point![x, y, z], vector![dx, dy, dz], f32::MAX, &opts)
+            })
+        });
+        if let Some(hit) = hit {
+            apply_damage(ctx, self.id(), hit.entity_id, 1);
         }
     }
 }
 
+/// Region of the caster's body; skills only act on that world
+fn caster_region(ctx: &ReducerContext) -> Option<u32> {
+    caster_entity(ctx)
+        .and_then(|id| ctx.db.physics_body().entity_id().find(id))
+        .map(|body| body.region)
+}
+
 // Build all skills once
 // Registry of all skills
 static SKILL_REGISTRY: Lazy<HashMap<SkillId, Box<dyn SkillBehavior>>> = Lazy::new(|| {
@@ -144,6 +151,8 @@ static SKILL_REGISTRY: Lazy<HashMap<SkillId, Box<dyn SkillBehavior>>> = Lazy::ne
 });
 
 #[reducer]
+/// Cast a skill; `view_at` is the server time of the world state the client showed when
+/// aiming, used to rewind hit queries within the region's rewind window
 pub fn use_skill(
     ctx: &ReducerContext,
     skill_id: SkillId,
@@ -153,11 +162,22 @@ pub fn use_skill(
     dx: f32,
     dy: f32,
     dz: f32,
+    view_at: Option<Timestamp>,
 ) -> Result<(), String> {
-    let now = ctx.timestamp;
     // Fetch skill behavior & base cooldown
     let behavior = SKILL_REGISTRY.get(&skill_id).ok_or("Unknown skill")?;
-    let default_base = behavior.base_ms();
+    start_cooldown(ctx, skill_id, behavior.base_ms())?;
+
+    // dispatch to the proper skill behavior
+    behavior.activate(ctx, x, y, z, dx, dy, dz, view_at);
+
+    Ok(())
+}
+
+/// Check the caster's cooldown for `skill_id` (after buffs) and, if ready, mark it used now.
+/// Shared by `use_skill` and the combat reducers.
+pub(crate) fn start_cooldown(ctx: &ReducerContext, skill_id: SkillId, default_base: u32) -> Result<(), String> {
+    let now = ctx.timestamp;
 
     // 1) find existing cooldown row
     let cd_row_opt = ctx.db.skill_cooldown().iter()
@@ -200,22 +220,30 @@ pub fn use_skill(
     } else {
         // Insert new row if it didn't exist
         let mut new_row = cd.to_row(ctx.sender, skill_id);
-        new_row.base_cooldown = behavior.base_ms();
+        new_row.base_cooldown = default_base;
         ctx.db.skill_cooldown().insert(new_row);
     }
-
-    // 5) dispatch to the proper skill behavior
-    behavior.activate(ctx, x, y, z, dx, dy, dz);
-
     Ok(())
 }
 
 /// Apply damage in two phases: batch health update and emit a timed event for clients
 pub(crate) fn apply_damage(ctx: &ReducerContext, skill_id: SkillId, target_entity: u32, amount: u32) {
-     // 1) accumulate pending damage for batched DB write
-     for world in PHYSICS_CONTEXTS.lock().unwrap().values_mut() {
-         *world.pending_damage.entry(target_entity).or_insert(0) += amount;
-     }
+    let Some(region) = ctx.db.physics_body().entity_id().find(target_entity).map(|b| b.region) else { return };
+    let mut worlds = PHYSICS_CONTEXTS.lock().unwrap();
+    if let Some(world) = worlds.get_mut(&region) {
+        apply_damage_in(ctx, world, skill_id, target_entity, amount);
+    }
+}
+
+/// `apply_damage` for callers already holding the target's world (physics tick, combat queries),
+/// since the PHYSICS_CONTEXTS lock can't be taken twice
+pub(crate) fn apply_damage_in(ctx: &ReducerContext, world: &mut PhysicsContext, skill_id: SkillId, target_entity: u32, amount: u32) {
+    // see `hooks::grant_invulnerability`
+    if world.invulnerable_until.contains_key(&target_entity) {
+        return;
+    }
+    // 1) accumulate pending damage for batched DB write
+    *world.pending_damage.entry(target_entity).or_insert(0) += amount;
 
     // 2) emit a DamageEvent with expire_at one second in the future
     if let Some(body_row) = ctx.db.physics_body().entity_id().find(target_entity) {
@@ -245,8 +273,6 @@ pub(crate) fn apply_buff(
     magnitude: f32,
     expires_at: Timestamp,
 ) -> u64 {
-    let new_id = BUFF_ID.fetch_add(1, Ordering::Relaxed);
-
     if buff_type == 4 { // Stacking buff type
         if let Some(mut existing) = ctx
             .db
@@ -260,6 +286,7 @@ pub(crate) fn apply_buff(
             return existing.id;
         } else {
             // For buff_type 4, if no existing entry is found, insert new record.
+            let new_id = next_id(ctx, Sequence::PlayerBuff);
             ctx.db.player_buffs().insert(PlayerBuff {
                 id: new_id,
                 player_id: player,
@@ -273,6 +300,7 @@ pub(crate) fn apply_buff(
     }
 
     // For other buff types, always insert a new record.
+    let new_id = next_id(ctx, Sequence::PlayerBuff);
     ctx.db.player_buffs().insert(PlayerBuff {
         id: new_id,
         player_id: player,
@@ -1,20 +1,35 @@
 use crate::physics::rapier_common::*;
 use rapier3d::prelude::*;
-use spacetimedb::{reducer, ReducerContext, Table};
-use crate::tables::physics_body::physics_body;
-use crate::physics::contact_tracker::register_owner;
-use crate::spacetime_common::shape::ColliderShape;
+use spacetimedb::{reducer, ReducerContext, Table, Timestamp};
+use crate::tables::physics_body::{physics_body, PhysicsBody};
+use crate::physics::contact_tracker::{end_entity_contacts, forget_collider_owners, forget_entity_contacts, register_owner};
+use crate::tables::contact_event::contact_event;
+use crate::tables::despawn_event::{despawn_event, DespawnEvent, DespawnReason};
+use crate::tables::player::{player, NO_PHYSICS_ENTITY};
+use crate::tables::player_buffs::player_buffs;
+use crate::physics::projectile::{configure_collider, register_projectile, DEFAULT_PROJECTILE_SPEC};
+use crate::tables::projectile::{projectile, ProjectileSpec};
+use crate::spacetime_common::shape::ColliderSpec;
+use crate::tables::physics_material::PhysicsMaterial;
+use crate::physics::assets::BodyResources;
+use crate::reducers::auth::{can_despawn, can_spawn, check_player_spawn, require_admin};
 use crate::spacetime_common::collision::*;
+use crate::spacetime_common::sequence::next_entity_id;
 
 pub use crate::physics::PHYSICS_CONTEXTS;
 pub use crate::physics::PhysicsContext;
+use crate::physics::existing_world;
+use crate::physics::joints::delete_joints_of;
+use crate::physics::character::CharacterState;
+use rapier3d::na::{Quaternion, Translation, UnitQuaternion};
 
-// Unique physics-entity ID counter
-static PHYSICS_ENTITY_COUNTER: Lazy<AtomicU64> = Lazy::new(|| AtomicU64::new(1));
+/// Upper bound on child colliders per body
+pub const MAX_COLLIDERS_PER_BODY: usize = 16;
 
-/// Build the Rapier RigidBodyBuilder for a given type & user_data
-fn make_rb_builder(body_type: u8, x: f32, y: f32, z: f32, ud: u128) -> RigidBodyBuilder {
-    let b = match body_type {
+/// Build the Rapier RigidBodyBuilder for a persisted row & user_data
+/// with the body properties of its material, if any
+fn make_rb_builder(row: &PhysicsBody, ud: u128, material: Option<&PhysicsMaterial>) -> RigidBodyBuilder {
+    let b = match row.body_type {
         STATIC_BODY_TYPE     => RigidBodyBuilder::fixed(),
         DYNAMIC_BODY_TYPE    => RigidBodyBuilder::dynamic(),
         KINEMATIC_BODY_TYPE  => RigidBodyBuilder::kinematic_position_based(),
@@ -22,39 +37,60 @@ fn make_rb_builder(body_type: u8, x: f32, y: f32, z: f32, ud: u128) -> RigidBody
         PLAYER_BODY_TYPE     => RigidBodyBuilder::kinematic_position_based(),
         _ => unreachable!(),
     };
-    b.translation(vector![x, y, z]).user_data(ud)
-}
-
-fn is_sensor_string(shape: &str) -> bool {
-    shape.to_lowercase().contains("sensor")
+    let rotation = UnitQuaternion::from_quaternion(Quaternion::new(row.rot_w, row.rot_x, row.rot_y, row.rot_z));
+    let position = Isometry::from_parts(Translation::from(vector![row.pos_x, row.pos_y, row.pos_z]), rotation);
+    let b = match material {
+        Some(m) => b
+            .linear_damping(m.linear_damping)
+            .angular_damping(m.angular_damping)
+            .gravity_scale(m.gravity_scale)
+            .enabled_rotations(!m.locked_rotations.x, !m.locked_rotations.y, !m.locked_rotations.z)
+            .ccd_enabled(m.ccd || row.body_type == PROJECTILE_BODY_TYPE),
+        None => b,
+    };
+    b.position(position)
+        .linvel(vector![row.vel_x, row.vel_y, row.vel_z])
+        .angvel(vector![row.ang_vel_x, row.ang_vel_y, row.ang_vel_z])
+        .user_data(ud)
 }
 
-#[reducer]
-pub fn spawn_rigid_body(
-    ctx: &ReducerContext,
-    region: u32,
-    x: f32,
-    y: f32,
-    z: f32,
-    collider_shape: String,
-    body_type: u8,
-) -> Result<(), String> {
+/// Build the rigid body and colliders described by a `physics_body` row and insert them
+/// into the given world. Shared by spawning and by world hydration after a restart.
+/// `res` holds the asset geometry and materials the row refers to (see `BodyResources::load`).
+pub(crate) fn insert_body(world: &mut PhysicsContext, row: &PhysicsBody, res: &BodyResources) -> Result<RigidBodyHandle, String> {
     // Validate body type
-    if ![0, 1, 2, 10, 20].contains(&body_type) {
+    if ![0, 1, 2, 10, 20].contains(&row.body_type) {
         return Err("Invalid body type".into());
     }
 
-    // Generate a unique ID for this physics entity via atomic counter
-    let entity_id = (PHYSICS_ENTITY_COUNTER.fetch_add(1, Ordering::Relaxed)) as u32;
+    if row.colliders.is_empty() || row.colliders.len() > MAX_COLLIDERS_PER_BODY {
+        return Err(format!("A body needs 1 to {} colliders", MAX_COLLIDERS_PER_BODY));
+    }
+
+    // Validate and build the colliders before touching the world so a bad row leaves it untouched;
+    // body type decides the groups unless a spec overrides them
+    let body_material = res.material(row.material.as_ref())?;
+    let mut col_builders = Vec::with_capacity(row.colliders.len());
+    for spec in &row.colliders {
+        spec.validate()?;
+        let groups = interaction_groups(row.body_type, spec.sensor);
+        let asset = spec.shape.asset_id().and_then(|id| res.assets.get(&id));
+        let surface = res.material(spec.material.as_ref())?.or(body_material);
+        // impact damage only from thresholds set by builders or by a server-defined material
+        let impact_threshold = spec.contact_force_threshold.filter(|_| res.trusted_specs)
+            .or(surface.and_then(|m| m.impact_threshold));
+        col_builders.push((spec, spec.to_rapier(asset, groups, surface, impact_threshold)?));
+    }
+
     // Pack user data for the rigid body
-    let object_function: u8 = 0; // Player on evrything for now since we use spawn_rigid_body at player creation
+    let object_function: u8 = 0; // the body itself has no role; each collider carries its own
     let tick_count: u8 = 0; // The tick count is not used
     let flag: bool = false; // No special flags for now
     let data = UserData {
-        body_type,
+        body_type: row.body_type,
         object_function,
         flag,
-        raw_id: entity_id,
+        raw_id: row.entity_id,
         modifier: 0, // No modifier for now
         hit_count: 0, // No hits yet
         block: false, // Not a block
@@ -62,39 +98,43 @@ pub fn spawn_rigid_body(
     };
     let packed_user_data = UserData::pack(data);
 
-    // Initialize or get the physics world for this region
-    let mut map = PHYSICS_CONTEXTS.lock().unwrap();
-    let world = map.entry(region)
-                                        .or_default();
-
-    let rb = make_rb_builder(body_type, x, y, z, packed_user_data).build();
+    let rb = make_rb_builder(row, packed_user_data, body_material).build();
     // Build and insert rigid body
     let body_handle = world.bodies.insert(rb);
     // Track handle for O(1) forward lookup
-    world.id_to_body.insert(entity_id, body_handle);
-    
-    // Parse and build collider from shape string
-    let sensor = is_sensor_string(&collider_shape);
-    let groups = interaction_groups(body_type, sensor);
-    let shape = collider_shape
-        .parse::<ColliderShape>()
-        .map_err(|e| e.to_string())?;
-    // Build collider and pack user_data
-    let col = shape.to_rapier(sensor, groups)
-        .user_data(packed_user_data)
-        .build();
-    // Insert collider into the physics world
-    let col_handle = world.colliders.insert_with_parent(col, body_handle, &mut world.bodies);
-    // tag the collider with client Identity for ownership tracking
-    register_owner(col_handle, ctx.sender);
-
-    // Calculate chunk coordinates for spatial partitioning
-    let (chunk_x, chunk_y) = calculate_chunk_pair(x, y);
+    world.id_to_body.insert(row.entity_id, body_handle);
 
-    // Insert row into physics_body
-    let phys = crate::tables::physics_body::PhysicsBody {
-        entity_id: entity_id,
-        owner_id: ctx.sender,
+    // Every child collider packs the parent's entity ID, so contacts resolve to the body
+    for (spec, builder) in col_builders {
+        let (object_function, flag, modifier) = (spec.object_function, spec.flag, spec.modifier);
+        let col = builder
+            .user_data(UserData { object_function, flag, modifier, ..data }.pack())
+            .build();
+        // Insert collider into the physics world
+        let col_handle = world.colliders.insert_with_parent(col, body_handle, &mut world.bodies);
+        // tag the collider with client Identity for ownership tracking
+        register_owner(row.region, col_handle, row.owner_id);
+    }
+
+    // player bodies are driven by the character controller
+    if row.body_type == PLAYER_BODY_TYPE {
+        world.characters.insert(row.entity_id, CharacterState {
+            grounded: row.grounded,
+            persisted_grounded: row.grounded,
+            ..CharacterState::default()
+        });
+    }
+
+    Ok(body_handle)
+}
+
+/// A physics_body row at rest at the given position; `spawn_body` assigns its entity_id
+pub(crate) fn new_body_row(owner_id: Identity, region: u32, x: f32, y: f32, z: f32, colliders: Vec<ColliderSpec>, body_type: u8) -> PhysicsBody {
+    // Calculate chunk coordinates for spatial partitioning (ground plane x/z)
+    let (chunk_x, chunk_y) = calculate_chunk_pair(x, z);
+    PhysicsBody {
+        entity_id: 0,
+        owner_id,
         health: 100,
         region,
         pos_x: x,
@@ -112,43 +152,232 @@ pub fn spawn_rigid_body(
         ang_vel_x: 0.0,
         ang_vel_y: 0.0,
         ang_vel_z: 0.0,
-        collider_shape: collider_shape.clone(),
+        colliders,
+        material: None,
         body_type,
-    };
-    ctx.db.physics_body().insert(phys);
+        grounded: false,
+    }
+}
 
-    log::info!("Physics object created: entity_id={}, shape={}, type={}", 
-        entity_id,
-        collider_shape, body_type);
+/// Spawn a body into its region's world and the physics_body table, returning its entity ID.
+/// Projectiles are tracked with `projectile` (or the default spec).
+pub(crate) fn spawn_body(ctx: &ReducerContext, mut row: PhysicsBody, projectile: Option<ProjectileSpec>) -> Result<u32, String> {
+    if ![row.pos_x, row.pos_y, row.pos_z, row.vel_x, row.vel_y, row.vel_z].iter().all(|v| v.is_finite()) {
+        return Err("Non-finite position or velocity".into());
+    }
+    // only dynamic bodies carry an initial velocity
+    if !matches!(row.body_type, DYNAMIC_BODY_TYPE | PROJECTILE_BODY_TYPE) {
+        (row.vel_x, row.vel_y, row.vel_z) = (0.0, 0.0, 0.0);
+    }
+
+    // Allocate a unique ID for this physics entity from the persisted sequence
+    row.entity_id = next_entity_id(ctx)?;
+
+    // Initialize or get the physics world for this region (which must already exist)
+    let mut map = PHYSICS_CONTEXTS.lock().unwrap();
+    let world = existing_world(ctx, &mut map, row.region)?;
+    let res = BodyResources::load(ctx, &row);
+    insert_body(world, &row, &res)?;
+
+    if row.body_type == PROJECTILE_BODY_TYPE {
+        let spec = projectile.unwrap_or(DEFAULT_PROJECTILE_SPEC);
+        configure_collider(world, row.entity_id, spec.on_impact);
+        register_projectile(ctx, &row, spec);
+    }
+
+    log::info!("Physics object created: entity_id={}, shape={}, colliders={}, type={}", 
+        row.entity_id,
+        row.colliders[0], row.colliders.len(), row.body_type);
+
+    // Insert row into physics_body
+    let entity_id = row.entity_id;
+    ctx.db.physics_body().insert(row);
+    Ok(entity_id)
+}
+
+#[reducer]
+#[allow(clippy::too_many_arguments)]
+/// Spawn a body owned by the caller; dynamic bodies and projectiles start with the given velocity.
+/// `material` names a physics_material row ("ice", "rubber", "metal", ...)
+pub fn spawn_rigid_body(
+    ctx: &ReducerContext,
+    region: u32,
+    x: f32,
+    y: f32,
+    z: f32,
+    collider: ColliderSpec,
+    body_type: u8,
+    vel_x: f32,
+    vel_y: f32,
+    vel_z: f32,
+    material: Option<String>,
+) -> Result<(), String> {
+    spawn_compound_body(ctx, region, x, y, z, vec![collider], body_type, vel_x, vel_y, vel_z, material)
+}
+
+#[reducer]
+#[allow(clippy::too_many_arguments)]
+/// Spawn one body with several child colliders (e.g. a solid capsule plus an aura sensor,
+/// or a vehicle built from boxes); the first collider is the primary one
+pub fn spawn_compound_body(
+    ctx: &ReducerContext,
+    region: u32,
+    x: f32,
+    y: f32,
+    z: f32,
+    colliders: Vec<ColliderSpec>,
+    body_type: u8,
+    vel_x: f32,
+    vel_y: f32,
+    vel_z: f32,
+    material: Option<String>,
+) -> Result<(), String> {
+    can_spawn(ctx, body_type)?;
+    let mut row = new_body_row(ctx.sender, region, x, y, z, colliders, body_type);
+    (row.vel_x, row.vel_y, row.vel_z) = (vel_x, vel_y, vel_z);
+    row.material = material;
+    check_player_spawn(ctx, &row)?;
+    spawn_body(ctx, row, None)?;
     Ok(())
 }
 
+#[reducer]
+#[allow(clippy::too_many_arguments)]
+/// Compatibility form of `spawn_rigid_body` taking the old shape grammar (`Box(1, 2, 3)`, `Sphere(3.0)Sensor`)
+pub fn spawn_rigid_body_str(
+    ctx: &ReducerContext,
+    region: u32,
+    x: f32,
+    y: f32,
+    z: f32,
+    collider_shape: String,
+    body_type: u8,
+    vel_x: f32,
+    vel_y: f32,
+    vel_z: f32,
+) -> Result<(), String> {
+    let collider = collider_shape.parse::<ColliderSpec>().map_err(|e| e.to_string())?;
+    spawn_rigid_body(ctx, region, x, y, z, collider, body_type, vel_x, vel_y, vel_z, None)
+}
+
+/// How long `despawn_event` rows stay for clients to read
+pub const DESPAWN_EVENT_TTL_MICROS: i64 = 10_000_000;
+
+/// Remove an entity's body, colliders and collider owners from a world along with every
+/// per-entity map and its rewind history
+pub(crate) fn remove_from_world(world: &mut PhysicsContext, entity_id: u32) {
+    // O(1) lookup via id_to_body map
+    if let Some(handle) = world.id_to_body.remove(&entity_id) {
+        let colliders: Vec<ColliderHandle> = world.bodies.get(handle).map_or_else(Vec::new, |b| b.colliders().to_vec());
+        forget_collider_owners(world.region, &colliders);
+        // freed handles get reused, so rewinds must not move a later collider to these poses
+        for snapshot in world.history.iter_mut() {
+            snapshot.poses.retain(|(h, _)| !colliders.contains(h));
+        }
+        // Safely remove the body and attached colliders
+        world.bodies.remove(
+            handle,
+            &mut world.islands,
+            &mut world.colliders,
+            &mut world.impulse_joints,
+            &mut world.multibody_joints,
+            true,
+        );
+        world.last_transforms.remove(&handle);
+        world.last_velocities.remove(&handle);
+        world.forced_bodies.retain(|h| *h != handle);
+        world.awake_bodies.retain(|h| *h != handle);
+    }
+    world.characters.remove(&entity_id);
+    world.pending_damage.remove(&entity_id);
+    world.invulnerable_until.remove(&entity_id);
+}
+
+/// Remove an entity everywhere in one place: open contacts are ended (aura buffs and
+/// contact_event rows go with them), then its Rapier body and colliders, every per-entity map
+/// of the world, collider owners, rewind history, joint and projectile rows, the owning
+/// player's `phy_entity_id` and its physics_body row. A `despawn_event` records why.
+pub(crate) fn despawn_entity(ctx: &ReducerContext, world: Option<&mut PhysicsContext>, entity_id: u32, reason: DespawnReason) {
+    let row = ctx.db.physics_body().entity_id().find(entity_id);
+    let mut world = world;
+    if let Some(world) = world.as_deref_mut() {
+        end_entity_contacts(ctx, world, entity_id);
+        remove_from_world(world, entity_id);
+    }
+    // Without a loaded world there is nothing to end; just drop any tracked state
+    forget_entity_contacts(entity_id);
+    // rows an unloaded world (or a restart) left behind
+    let events: Vec<u64> = ctx.db.contact_event().entity_1().filter(entity_id)
+        .chain(ctx.db.contact_event().entity_2().filter(entity_id))
+        .map(|e| e.id)
+        .collect();
+    for id in events {
+        ctx.db.contact_event().id().delete(id);
+    }
+    // Rapier dropped any attached joints with the body; drop their rows too
+    delete_joints_of(ctx, world, entity_id);
+    ctx.db.projectile().entity_id().delete(entity_id);
+
+    let Some(row) = row else { return };
+    if row.body_type == PLAYER_BODY_TYPE {
+        // a gone body stands in no aura; drop the open-ended aura buffs it was given
+        for buff in ctx.db.player_buffs().player_id().filter(row.owner_id)
+            .filter(|b| b.buff_type == object_function::AURA && b.expires_at == Timestamp::from_micros_since_unix_epoch(i64::MAX))
+        {
+            ctx.db.player_buffs().id().delete(buff.id);
+        }
+    }
+    for mut player in ctx.db.player().phy_entity_id().filter(entity_id).collect::<Vec<_>>() {
+        player.phy_entity_id = NO_PHYSICS_ENTITY;
+        ctx.db.player().player_id().update(player);
+    }
+    ctx.db.despawn_event().insert(DespawnEvent {
+        event_id: 0,
+        entity_id,
+        owner_id: row.owner_id,
+        region: row.region,
+        body_type: row.body_type,
+        reason,
+        despawned_at: ctx.timestamp,
+        expire_at: Timestamp::from_micros_since_unix_epoch(ctx.timestamp.to_micros_since_unix_epoch() + DESPAWN_EVENT_TTL_MICROS),
+    });
+    log::debug!("Despawned entity {} ({:?})", entity_id, reason);
+    // Delete from the PhysicsBody table
+    ctx.db.physics_body().entity_id().delete(entity_id);
+}
+
+/// Despawn every entity queued in `pending_despawns` during this tick
+pub(crate) fn flush_pending_despawns(ctx: &ReducerContext, world: &mut PhysicsContext) {
+    let mut queued = std::mem::take(&mut world.pending_despawns);
+    // an entity queued twice keeps the first reason
+    queued.sort_by_key(|(entity_id, _)| *entity_id);
+    queued.dedup_by_key(|(entity_id, _)| *entity_id);
+    for (entity_id, reason) in queued {
+        despawn_entity(ctx, Some(world), entity_id, reason);
+    }
+}
+
 #[reducer]
 /// Remove a rigid body and its collider from the physics world and delete its DB entry
+/// (own bodies of a type the caller may spawn; admins any body)
 pub fn despawn_rigid_body(
     ctx: &ReducerContext,
     entity_id: u32,
     region: u32,
 ) -> Result<(), String> {
+    // The row knows the authoritative region; fall back to the caller's for orphaned bodies
+    let region = match ctx.db.physics_body().entity_id().find(entity_id) {
+        Some(row) => {
+            can_despawn(ctx, &row)?;
+            row.region
+        }
+        None => {
+            require_admin(ctx)?;
+            region
+        }
+    };
     // Lock and get the physics context for this region
     let mut map = PHYSICS_CONTEXTS.lock().unwrap();
-    if let Some(world) = map.get_mut(&region) {
-        // O(1) lookup via id_to_body map
-        if let Some(&handle) = world.id_to_body.get(&entity_id) {
-            // Safely remove the body and attached colliders
-            world.bodies.remove(
-                handle,
-                &mut world.islands,
-                &mut world.colliders,
-                &mut world.impulse_joints,
-                &mut world.multibody_joints,
-                true,
-            );
-            // Remove forward lookup entry
-            world.id_to_body.remove(&entity_id);
-        }
-    }
-    // Delete from the PhysicsBody table
-    ctx.db.physics_body().entity_id().delete(entity_id);
+    despawn_entity(ctx, map.get_mut(&region), entity_id, DespawnReason::Requested);
     Ok(())
-}
\ No newline at end of file
+}
@@ -59,4 +59,883 @@ fn test_contact_tracking() {
     // // Check contact_duration table for records
     // let contacts = ctx.db.contact_duration().iter().collect::<Vec<_>>();
     // assert!(!contacts.is_empty(), "Should have created contact records");
-}
\ No newline at end of file
+}
+use crate::spacetime_common::shape::{ColliderShape, ColliderSpec, Vec3};
+
+/// Legacy shape strings parse into specs and print back in canonical form
+#[test]
+fn test_collider_spec_string_round_trip() {
+    for (input, canonical) in [
+        ("Sphere(0.5)", "Sphere(0.5)"),
+        ("Box(1000, 0.1, 1000)", "Box(1000, 0.1, 1000)"),
+        ("Box(1,2,3)", "Box(1, 2, 3)"),
+        ("Sphere(3.0)Sensor", "Sphere(3)Sensor"),
+        ("Sphere(3.0) sensor", "Sphere(3)Sensor"),
+        ("Capsule(0.9, 0.3)", "Capsule(0.9, 0.3)"),
+        ("Cylinder(1,0.5)Sensor", "Cylinder(1, 0.5)Sensor"),
+        ("Cone(2, 1)", "Cone(2, 1)"),
+        ("ConvexHull(7)", "ConvexHull(7)"),
+        ("TriMesh( 12 )", "TriMesh(12)"),
+        ("HeightField(3)", "HeightField(3)"),
+    ] {
+        let spec: ColliderSpec = input.parse().unwrap();
+        assert_eq!(spec.to_string(), canonical);
+        assert_eq!(canonical.parse::<ColliderSpec>().unwrap(), spec);
+    }
+
+    let spec: ColliderSpec = "Sphere(3.0)Sensor".parse().unwrap();
+    assert_eq!(spec.shape, ColliderShape::Sphere(3.0));
+    assert!(spec.sensor);
+    let spec: ColliderSpec = "Box(1, 2, 3)".parse().unwrap();
+    assert_eq!(spec.shape, ColliderShape::Cuboid(Vec3::new(1.0, 2.0, 3.0)));
+    assert!(!spec.sensor);
+}
+
+#[test]
+fn test_collider_spec_rejects_bad_input() {
+    for input in ["", "Sphere", "Sphere(x)", "Box(1, 2)", "Capsule(1)", "TriMesh(-1)", "Torus(1, 2)", "Sensor"] {
+        assert!(input.parse::<ColliderSpec>().is_err(), "{input} should not parse");
+    }
+    assert!("Sphere(-1)".parse::<ColliderSpec>().unwrap().validate().is_err());
+    assert!("Box(1, 0, 1)".parse::<ColliderSpec>().unwrap().validate().is_err());
+    assert!("Cone(1, -2)".parse::<ColliderSpec>().unwrap().validate().is_err());
+}
+
+/// Conveyors carry resting bodies along their local +X; one-way platforms let bodies rise through
+#[test]
+fn test_hooks_conveyor_and_one_way_platform() {
+    use rapier3d::prelude::*;
+    use crate::physics::{step_world, PhysicsContext};
+    use crate::physics::rapier_common::UserData;
+    use crate::spacetime_common::collision::{object_function, DYNAMIC_BODY_TYPE, STATIC_BODY_TYPE};
+    use crate::spacetime_common::shape::{ColliderShape, Vec3};
+
+    let hooked = |shape: ColliderShape| shape.to_rapier(None, false, InteractionGroups::all()).unwrap();
+    let data = |body_type, raw_id, object_function, modifier| UserData {
+        body_type, object_function, flag: false, raw_id, hit_count: 0, modifier, block: false, tick_count: 0,
+    }.pack();
+
+    let mut world = PhysicsContext::default();
+    let add = |world: &mut PhysicsContext, rb: RigidBodyBuilder, col: ColliderBuilder| {
+        let h = world.bodies.insert(rb.build());
+        world.colliders.insert_with_parent(col.build(), h, &mut world.bodies);
+        h
+    };
+    // conveyor at 2 m/s (modifier 20) with a box resting on it
+    add(&mut world, RigidBodyBuilder::fixed(),
+        hooked(ColliderShape::Cuboid(Vec3::new(20.0, 1.0, 4.0))).user_data(data(STATIC_BODY_TYPE, 1, object_function::CONVEYOR, 20)));
+    let crate_box = add(&mut world, RigidBodyBuilder::dynamic().translation(vector![0.0, 1.0, 0.0]),
+        hooked(ColliderShape::Cuboid(Vec3::new(1.0, 1.0, 1.0))).user_data(data(DYNAMIC_BODY_TYPE, 2, 0, 0)));
+    // one-way platform high above with a ball launched up from beneath it
+    add(&mut world, RigidBodyBuilder::fixed().translation(vector![0.0, 20.0, 0.0]),
+        hooked(ColliderShape::Cuboid(Vec3::new(4.0, 0.5, 4.0))).user_data(data(STATIC_BODY_TYPE, 3, object_function::ONE_WAY_PLATFORM, 0)));
+    let ball = add(&mut world, RigidBodyBuilder::dynamic().translation(vector![0.0, 18.0, 0.0]).linvel(vector![0.0, 12.0, 0.0]),
+        hooked(ColliderShape::Sphere(0.4)).user_data(data(DYNAMIC_BODY_TYPE, 4, 0, 0)));
+
+    for _ in 0..120 {
+        step_world(&mut world, &());
+    }
+    let carried = world.bodies[crate_box].translation().x;
+    assert!(carried > 1.0, "conveyor should carry the box along +X, got x = {}", carried);
+    let ball_y = world.bodies[ball].translation().y;
+    assert!(ball_y > 20.0, "ball should pass up through and land on the platform, got y = {}", ball_y);
+}
+
+/// Rewound queries see bodies where they were at the requested time, within the window
+#[test]
+fn test_rewind_history_queries_past_positions() {
+    use rapier3d::prelude::*;
+    use spacetimedb::Timestamp;
+    use crate::physics::PhysicsContext;
+    use crate::physics::queries::{raycast, QueryOptions};
+    use crate::physics::rapier_common::pack_id;
+    use crate::physics::rewind::{record_history, with_rewound};
+
+    let mut world = PhysicsContext::default();
+    let body = world.bodies.insert(RigidBodyBuilder::kinematic_position_based().build());
+    world.colliders.insert_with_parent(ColliderBuilder::ball(0.5).user_data(pack_id(7)).build(), body, &mut world.bodies);
+    let at = |ms: i64| Timestamp::from_micros_since_unix_epoch(ms * 1000);
+    // the target moves from x = 0 to x = 10 between two ticks 100 ms apart
+    let tick = |world: &mut PhysicsContext, x: Real, ms: i64| {
+        world.bodies[body].set_translation(vector![x, 0.0, 0.0], true);
+        world.colliders.iter_mut().for_each(|(_, c)| c.set_position(Isometry::translation(x, 0.0, 0.0)));
+        world.query_pipeline.update(&world.colliders);
+        record_history(world, at(ms));
+    };
+    tick(&mut world, 0.0, 1_000);
+    tick(&mut world, 10.0, 1_100);
+
+    let ray_at = |w: &PhysicsContext, x: Real| raycast(w, point![x, 5.0, 0.0], vector![0.0, -1.0, 0.0], 10.0, &QueryOptions::default());
+    let now = at(1_100);
+    assert!(with_rewound(&mut world, now, Some(at(1_000)), |w| ray_at(w, 0.0)).is_some_and(|h| h.entity_id == 7));
+    assert!(with_rewound(&mut world, now, Some(at(1_050)), |w| ray_at(w, 5.0)).is_some());
+    // a view time older than the window is clamped to the window start (250 ms), not honoured
+    let clamped = with_rewound(&mut world, at(1_300), Some(at(0)), |w| (ray_at(w, 5.0), ray_at(w, 0.0)));
+    assert!(clamped.0.is_some() && clamped.1.is_none());
+    // the present is restored afterwards
+    assert!(ray_at(&world, 10.0).is_some() && ray_at(&world, 0.0).is_none());
+}
+
+/// Sleeping bodies never reach the DB-write pass; bodies that just fell asleep get one last write
+#[test]
+fn test_dirty_bodies_follow_island_sleep_state() {
+    use rapier3d::prelude::*;
+    use crate::physics::{dirty_bodies, step_world, PhysicsContext};
+
+    let mut world = PhysicsContext::default();
+    let add = |world: &mut PhysicsContext, rb: RigidBodyBuilder| {
+        let h = world.bodies.insert(rb.build());
+        world.colliders.insert_with_parent(ColliderBuilder::ball(0.5).build(), h, &mut world.bodies);
+        h
+    };
+    let asleep = add(&mut world, RigidBodyBuilder::dynamic().translation(vector![0.0, 10.0, 0.0]).sleeping(true));
+    let falling = add(&mut world, RigidBodyBuilder::dynamic().translation(vector![5.0, 10.0, 0.0]));
+    step_world(&mut world, &());
+
+    let dirty = dirty_bodies(&mut world);
+    assert!(dirty.contains(&falling) && !dirty.contains(&asleep));
+
+    world.bodies[falling].sleep();
+    step_world(&mut world, &());
+    assert!(dirty_bodies(&mut world).contains(&falling), "final write after falling asleep");
+    assert!(dirty_bodies(&mut world).is_empty());
+}
+
+/// Spawn permissions per body type: players only projectiles, static geometry admin-only
+#[test]
+fn test_spawn_roles_per_body_type() {
+    use crate::reducers::auth::spawn_role;
+    use crate::spacetime_common::collision::*;
+    use crate::tables::user_role::Role;
+
+    assert_eq!(spawn_role(PROJECTILE_BODY_TYPE), Some(Role::Player));
+    assert_eq!(spawn_role(DYNAMIC_BODY_TYPE), Some(Role::Builder));
+    assert_eq!(spawn_role(KINEMATIC_BODY_TYPE), Some(Role::Builder));
+    assert_eq!(spawn_role(STATIC_BODY_TYPE), Some(Role::Admin));
+    assert_eq!(spawn_role(PLAYER_BODY_TYPE), None);
+    assert!(Role::Player < Role::Builder && Role::Builder < Role::Admin);
+}
+
+/// Walking input moves a character across the ground plane (x/z) and into the next chunk
+#[test]
+fn test_input_moves_character_across_chunk_boundary() {
+    use rapier3d::prelude::*;
+    use crate::physics::character::{input_translation, move_characters, CharacterState};
+    use crate::physics::{chunk_of, step_world, PhysicsContext};
+
+    let mut world = PhysicsContext::default();
+    let floor = world.bodies.insert(RigidBodyBuilder::fixed().translation(vector![0.0, -0.05, 0.0]).build());
+    world.colliders.insert_with_parent(ColliderBuilder::cuboid(50.0, 0.05, 50.0).build(), floor, &mut world.bodies);
+    let player = world.bodies.insert(RigidBodyBuilder::kinematic_position_based().translation(vector![5.0, 0.5, 9.0]).build());
+    world.colliders.insert_with_parent(ColliderBuilder::ball(0.5).build(), player, &mut world.bodies);
+    world.id_to_body.insert(1, player);
+    world.characters.insert(1, CharacterState { grounded: true, ..CharacterState::default() });
+
+    let chunk = |world: &PhysicsContext| chunk_of(world.bodies[player].translation());
+    assert_eq!(chunk(&world), (0, 0));
+
+    // one second of walking along +z (an up component in the input is ignored)
+    let dt = world.integration_parameters.dt;
+    let up = world.character_controller.up;
+    for _ in 0..60 {
+        world.query_pipeline.update(&world.colliders);
+        world.characters.get_mut(&1).unwrap().desired_translation = Some(input_translation(vector![0.0, 1.0, 1.0], false, &up, dt));
+        move_characters(&mut world, dt);
+        step_world(&mut world, &());
+    }
+    let pos = world.bodies[player].translation();
+    assert_eq!(chunk(&world), (0, 1), "walked to {:?}", pos);
+    assert!((pos.y - 0.5).abs() < 0.1, "walking must not change height, got y = {}", pos.y);
+}
+
+/// A region whose bodies are still moving is not at rest, so it won't go dormant yet
+#[test]
+fn test_moving_body_keeps_region_awake() {
+    use rapier3d::prelude::*;
+    use crate::physics::dormancy::world_at_rest;
+    use crate::physics::{step_world, PhysicsContext};
+    use crate::physics::rapier_common::UserData;
+    use crate::spacetime_common::collision::{DYNAMIC_BODY_TYPE, PROJECTILE_BODY_TYPE};
+
+    let data = |body_type| UserData {
+        body_type, object_function: 0, flag: false, raw_id: 1, hit_count: 0, modifier: 0, block: false, tick_count: 0,
+    }.pack();
+    let mut world = PhysicsContext::default();
+    let falling = world.bodies.insert(RigidBodyBuilder::dynamic().translation(vector![0.0, 10.0, 0.0]).user_data(data(DYNAMIC_BODY_TYPE)).build());
+    world.colliders.insert_with_parent(ColliderBuilder::ball(0.5).build(), falling, &mut world.bodies);
+    step_world(&mut world, &());
+    assert!(!world_at_rest(&world), "a falling body keeps the region awake");
+
+    world.bodies[falling].sleep();
+    step_world(&mut world, &());
+    assert!(world_at_rest(&world));
+
+    // a projectile in flight keeps it awake even while asleep
+    let projectile = world.bodies.insert(RigidBodyBuilder::dynamic().sleeping(true).user_data(data(PROJECTILE_BODY_TYPE)).build());
+    world.colliders.insert_with_parent(ColliderBuilder::ball(0.1).build(), projectile, &mut world.bodies);
+    step_world(&mut world, &());
+    assert!(!world_at_rest(&world));
+}
+
+/// Below Builder only small plain colliders may be spawned
+#[test]
+fn test_player_collider_limits() {
+    use crate::reducers::auth::{check_limit, player_collider_allowed, PLAYER_MAX_SPEED};
+    use crate::spacetime_common::collision::object_function;
+    use crate::spacetime_common::shape::{AxialSize, ColliderShape, ColliderSpec, CollisionGroups, Vec3};
+
+    let small = ColliderSpec::new(ColliderShape::Sphere(0.2));
+    assert!(player_collider_allowed(&small).is_ok());
+    assert!(player_collider_allowed(&ColliderSpec::new(ColliderShape::Capsule(AxialSize { half_height: 0.5, radius: 0.3 }))).is_ok());
+
+    for spec in [
+        ColliderSpec { object_function: object_function::DAMAGE_OVER_TIME, ..small.clone() },
+        ColliderSpec { object_function: object_function::AURA, ..small.clone() },
+        ColliderSpec { flag: true, ..small.clone() },
+        ColliderSpec { modifier: 3, ..small.clone() },
+        ColliderSpec { groups: Some(CollisionGroups { memberships: 1, filter: u32::MAX }), ..small.clone() },
+        ColliderSpec { offset: Vec3::new(0.0, 5.0, 0.0), ..small.clone() },
+        ColliderSpec::new(ColliderShape::Cuboid(Vec3::new(1000.0, 0.1, 1000.0))),
+        ColliderSpec::new(ColliderShape::TriMesh(1)),
+        ColliderSpec { contact_force_threshold: Some(0.0), ..small.clone() },
+    ] {
+        assert!(player_collider_allowed(&spec).is_err(), "{:?} should be rejected", spec);
+    }
+    assert!(check_limit("Speed", [3.0, 0.0, 4.0], 5.0).is_ok());
+    assert!(check_limit("Speed", [0.0, PLAYER_MAX_SPEED, 1.0], PLAYER_MAX_SPEED).is_err());
+}
+
+/// Impact damage starts above the minimum impulse, scales per N·s and is capped
+#[test]
+fn test_impact_damage_mapping() {
+    use crate::physics::contact_tracker::*;
+
+    assert_eq!(impact_damage(0.0), 0);
+    assert_eq!(impact_damage(IMPACT_DAMAGE_MIN_IMPULSE), 0);
+    assert_eq!(impact_damage(IMPACT_DAMAGE_MIN_IMPULSE + 5.0 / IMPACT_DAMAGE_PER_IMPULSE), 5);
+    assert_eq!(impact_damage(IMPACT_DAMAGE_MIN_IMPULSE + 40.0 / IMPACT_DAMAGE_PER_IMPULSE), 40);
+    let at_cap = IMPACT_DAMAGE_MIN_IMPULSE + IMPACT_DAMAGE_MAX as f32 / IMPACT_DAMAGE_PER_IMPULSE;
+    assert_eq!(impact_damage(at_cap), IMPACT_DAMAGE_MAX);
+    assert_eq!(impact_damage(at_cap * 10.0), IMPACT_DAMAGE_MAX);
+}
+
+/// Joints with non-finite values, inverted limits or negative lengths and forces are rejected
+#[test]
+fn test_build_joint_validation() {
+    use spacetimedb::Identity;
+    use crate::physics::joints::build_joint;
+    use crate::tables::physics_joint::{JointKind, JointLimitSpec, JointMotorSpec, PhysicsJoint};
+
+    let hinge = PhysicsJoint {
+        joint_id: 0,
+        region: 0,
+        body_a: 1,
+        body_b: 2,
+        owner_id: Identity::ZERO,
+        kind: JointKind::Revolute,
+        anchor_a_x: 0.0, anchor_a_y: 1.0, anchor_a_z: 0.0,
+        anchor_b_x: 0.0, anchor_b_y: -1.0, anchor_b_z: 0.0,
+        axis_x: 0.0, axis_y: 1.0, axis_z: 0.0,
+        limits: Some(JointLimitSpec { min: -1.0, max: 1.0 }),
+        motor: Some(JointMotorSpec { target_pos: 0.0, target_vel: 1.0, stiffness: 0.0, damping: 1.0, max_force: 10.0 }),
+        contacts_enabled: false,
+    };
+    let rope = PhysicsJoint { kind: JointKind::Rope, limits: Some(JointLimitSpec { min: 0.0, max: 3.0 }), motor: None, ..hinge.clone() };
+    assert!(build_joint(&hinge).is_ok());
+    assert!(build_joint(&rope).is_ok());
+
+    let motor = hinge.motor.unwrap();
+    for bad in [
+        PhysicsJoint { anchor_a_x: f32::NAN, ..hinge.clone() },
+        PhysicsJoint { anchor_b_z: f32::INFINITY, ..hinge.clone() },
+        PhysicsJoint { axis_x: 0.0, axis_y: 0.0, axis_z: 0.0, ..hinge.clone() },
+        PhysicsJoint { axis_y: f32::NAN, ..hinge.clone() },
+        PhysicsJoint { limits: Some(JointLimitSpec { min: 1.0, max: -1.0 }), ..hinge.clone() },
+        PhysicsJoint { limits: Some(JointLimitSpec { min: 0.0, max: f32::INFINITY }), ..hinge.clone() },
+        PhysicsJoint { motor: Some(JointMotorSpec { target_vel: f32::NAN, ..motor }), ..hinge.clone() },
+        PhysicsJoint { motor: Some(JointMotorSpec { stiffness: f32::INFINITY, ..motor }), ..hinge.clone() },
+        PhysicsJoint { motor: Some(JointMotorSpec { max_force: -1.0, ..motor }), ..hinge.clone() },
+        PhysicsJoint { limits: Some(JointLimitSpec { min: -2.0, max: -1.0 }), ..rope.clone() },
+        PhysicsJoint { limits: None, ..rope.clone() },
+        PhysicsJoint { kind: JointKind::Fixed, ..hinge.clone() },
+    ] {
+        assert!(build_joint(&bad).is_err(), "{:?} should be rejected", bad);
+    }
+}
+
+/// Invulnerability and a pending tick force move with a body handed off to another region
+#[test]
+fn test_handoff_carries_invulnerability_and_forces() {
+    use rapier3d::prelude::*;
+    use spacetimedb::Timestamp;
+    use crate::physics::handoff::{attach_body, detach_body};
+    use crate::physics::hooks::{expire_invulnerability, set_invulnerable};
+    use crate::physics::motion::add_force_to;
+    use crate::physics::rapier_common::{get_block, UserData};
+    use crate::physics::PhysicsContext;
+    use crate::spacetime_common::collision::DYNAMIC_BODY_TYPE;
+
+    let data = UserData {
+        body_type: DYNAMIC_BODY_TYPE, object_function: 0, flag: false, raw_id: 7, hit_count: 0, modifier: 0, block: false, tick_count: 0,
+    }.pack();
+    let mut from = PhysicsContext { region: 1, ..PhysicsContext::default() };
+    let mut to = PhysicsContext { region: 2, ..PhysicsContext::default() };
+    let handle = from.bodies.insert(RigidBodyBuilder::dynamic().user_data(data).build());
+    from.colliders.insert_with_parent(ColliderBuilder::ball(0.5).user_data(data).build(), handle, &mut from.bodies);
+    from.id_to_body.insert(7, handle);
+
+    let until = Timestamp::from_micros_since_unix_epoch(1_000_000);
+    set_invulnerable(&mut from, 7, true);
+    from.invulnerable_until.insert(7, until);
+    add_force_to(&mut from, 7, vector![1.0, 0.0, 0.0]).unwrap();
+
+    attach_body(&mut to, detach_body(&mut from, handle).unwrap());
+    assert!(from.invulnerable_until.is_empty() && from.forced_bodies.is_empty());
+    let moved = to.id_to_body[&7];
+    assert_eq!(to.invulnerable_until.get(&7), Some(&until));
+    assert_eq!(to.forced_bodies, vec![moved]);
+    let collider = to.bodies[moved].colliders()[0];
+    assert!(get_block(to.colliders[collider].user_data));
+
+    // the new world clears the block bit once invulnerability runs out
+    expire_invulnerability(&mut to, until);
+    assert!(!get_block(to.colliders[collider].user_data));
+}
+
+/// Contacts between bodies handed off together carry on in the new region without a new Start
+#[test]
+fn test_handoff_keeps_contacts_between_bodies_moving_together() {
+    use std::collections::HashMap;
+    use crossbeam::channel::unbounded;
+    use rapier3d::prelude::*;
+    use crate::physics::contact_tracker::{move_contacts, process_contacts, PhysicsContact};
+    use crate::physics::handoff::{attach_body, detach_body};
+    use crate::physics::rapier_common::UserData;
+    use crate::physics::{step_world, PhysicsContext};
+    use crate::spacetime_common::collision::DYNAMIC_BODY_TYPE;
+
+    // regions unique to this test, since the contact map is shared
+    let (from_region, to_region) = (61, 62);
+    let mut from = PhysicsContext { region: from_region, ..PhysicsContext::default() };
+    let mut to = PhysicsContext { region: to_region, ..PhysicsContext::default() };
+    let mut body = |id: u32, sensor: bool| {
+        let data = UserData {
+            body_type: DYNAMIC_BODY_TYPE, object_function: 0, flag: false, raw_id: id, hit_count: 0, modifier: 0, block: false, tick_count: 0,
+        }.pack();
+        let handle = from.bodies.insert(RigidBodyBuilder::dynamic().gravity_scale(0.0).user_data(data).build());
+        let collider = ColliderBuilder::ball(1.0).sensor(sensor).active_events(ActiveEvents::COLLISION_EVENTS).user_data(data).build();
+        from.colliders.insert_with_parent(collider, handle, &mut from.bodies);
+        from.id_to_body.insert(id, handle);
+        handle
+    };
+    let (aura, walker) = (body(1, true), body(2, false));
+
+    let step = |world: &mut PhysicsContext| {
+        let (collision_tx, collision_rx) = unbounded();
+        let (force_tx, _force_rx) = unbounded();
+        step_world(world, &ChannelEventCollector::new(collision_tx, force_tx));
+        let events: Vec<_> = collision_rx.try_iter().collect();
+        process_contacts(&events, world, world.region)
+    };
+    let started = step(&mut from);
+    assert!(started.iter().any(|c| matches!(c, PhysicsContact::Start { unpacked_source_id: 1, unpacked_target_id: 2, .. })));
+
+    let mut moved = HashMap::new();
+    for handle in [aura, walker] {
+        moved.extend(attach_body(&mut to, detach_body(&mut from, handle).unwrap()));
+    }
+    move_contacts(from_region, to_region, &moved);
+
+    let carried = step(&mut to);
+    assert!(!carried.iter().any(|c| matches!(c, PhysicsContact::Start { .. })), "no second Start after the handoff");
+    assert!(carried.iter().any(|c| matches!(c, PhysicsContact::Continue { unpacked_source_id: 1, unpacked_target_id: 2, tick_count: 2, .. })));
+    assert!(step(&mut from).is_empty(), "nothing left behind in the old region");
+}
+
+/// Query reducers reject oversized shapes and distances
+#[test]
+fn test_query_size_limits() {
+    use crate::physics::queries::{check_cast, to_shape, MAX_QUERY_DISTANCE, MAX_QUERY_EXTENT};
+    use crate::tables::query_hit::{HalfExtents, QueryShape};
+
+    assert!(to_shape(QueryShape::Sphere(MAX_QUERY_EXTENT)).is_ok());
+    assert!(to_shape(QueryShape::Sphere(MAX_QUERY_EXTENT * 2.0)).is_err());
+    assert!(to_shape(QueryShape::Cuboid(HalfExtents { hx: 1.0, hy: 1.0e6, hz: 1.0 })).is_err());
+    assert!(to_shape(QueryShape::Sphere(0.0)).is_err());
+    assert!(check_cast(&[0.0, 1.0, 0.0], MAX_QUERY_DISTANCE).is_ok());
+    assert!(check_cast(&[0.0, 1.0, 0.0], MAX_QUERY_DISTANCE + 1.0).is_err());
+    assert!(check_cast(&[f32::NAN], 1.0).is_err());
+}
+
+/// A persisted row rebuilds its body with transform, velocity, packed user data and id_to_body
+#[test]
+fn test_insert_body_restores_row_state() {
+    use rapier3d::prelude::*;
+    use spacetimedb::Identity;
+    use crate::physics::assets::BodyResources;
+    use crate::physics::rapier_common::{get_body_type, unpack_id};
+    use crate::physics::spawn::{insert_body, new_body_row};
+    use crate::physics::PhysicsContext;
+    use crate::spacetime_common::collision::DYNAMIC_BODY_TYPE;
+    use crate::spacetime_common::shape::{ColliderShape, ColliderSpec};
+
+    let mut row = new_body_row(Identity::ZERO, 0, 3.0, 4.0, 5.0, vec![ColliderSpec::new(ColliderShape::Sphere(0.5))], DYNAMIC_BODY_TYPE);
+    row.entity_id = 42;
+    // a quarter turn around y
+    let half = std::f32::consts::FRAC_PI_4;
+    (row.rot_x, row.rot_y, row.rot_z, row.rot_w) = (0.0, half.sin(), 0.0, half.cos());
+    (row.vel_x, row.vel_y, row.vel_z) = (1.0, 2.0, 3.0);
+    (row.ang_vel_x, row.ang_vel_y, row.ang_vel_z) = (0.0, 0.5, 0.0);
+
+    let mut world = PhysicsContext::default();
+    let handle = insert_body(&mut world, &row, &BodyResources::default()).unwrap();
+    assert_eq!(world.id_to_body.get(&42), Some(&handle));
+
+    let body = &world.bodies[handle];
+    assert_eq!(*body.translation(), vector![3.0, 4.0, 5.0]);
+    assert!((body.rotation().angle() - std::f32::consts::FRAC_PI_2).abs() < 1.0e-5);
+    assert_eq!(*body.linvel(), vector![1.0, 2.0, 3.0]);
+    assert_eq!(*body.angvel(), vector![0.0, 0.5, 0.0]);
+    assert_eq!((unpack_id(body.user_data), get_body_type(body.user_data)), (42, DYNAMIC_BODY_TYPE));
+    let collider = &world.colliders[body.colliders()[0]];
+    assert_eq!(unpack_id(collider.user_data), 42);
+
+    // an unknown body type leaves the world untouched
+    row.body_type = 99;
+    assert!(insert_body(&mut world, &row, &BodyResources::default()).is_err());
+    assert_eq!(world.bodies.len(), 1);
+}
+
+/// Velocity jitter below `VELOCITY_EPSILON` on every component doesn't count as a change
+#[test]
+fn test_velocity_write_epsilon() {
+    use rapier3d::prelude::*;
+    use crate::physics::{velocity_exceeds_epsilon, VELOCITY_EPSILON};
+
+    let old = vector![1.0, -2.0, 0.0];
+    assert!(!velocity_exceeds_epsilon(&old, &old));
+    assert!(!velocity_exceeds_epsilon(&old, &(old + vector![VELOCITY_EPSILON * 0.5, -VELOCITY_EPSILON * 0.5, 0.0])));
+    assert!(velocity_exceeds_epsilon(&old, &(old + vector![0.0, 0.0, VELOCITY_EPSILON * 2.0])));
+    assert!(velocity_exceeds_epsilon(&old, &(old - vector![VELOCITY_EPSILON * 2.0, 0.0, 0.0])));
+    // coming to rest is a change worth writing
+    assert!(velocity_exceeds_epsilon(&old, &Vector::zeros()));
+}
+
+/// Sub-epsilon drift in translation or rotation doesn't count as a transform change
+#[test]
+fn test_transform_write_epsilon() {
+    use rapier3d::prelude::*;
+    use crate::physics::{transform_exceeds_epsilon, POSITION_EPSILON, ROTATION_EPSILON};
+
+    let pos = vector![3.0, 1.0, -4.0];
+    let rot = Rotation::identity();
+    assert!(!transform_exceeds_epsilon(&pos, &rot, &pos, &rot));
+    let nudged = pos + vector![POSITION_EPSILON * 0.5, 0.0, -POSITION_EPSILON * 0.5];
+    assert!(!transform_exceeds_epsilon(&pos, &rot, &nudged, &rot));
+    assert!(transform_exceeds_epsilon(&pos, &rot, &(pos + vector![0.0, POSITION_EPSILON * 2.0, 0.0]), &rot));
+
+    let turned = |angle: Real| Rotation::from_axis_angle(&Vector::y_axis(), angle);
+    assert!(!transform_exceeds_epsilon(&pos, &rot, &pos, &turned(ROTATION_EPSILON * 0.5)));
+    assert!(transform_exceeds_epsilon(&pos, &rot, &pos, &turned(ROTATION_EPSILON * 2.0)));
+}
+
+/// Sequence values beyond 32 bits are refused instead of being truncated into user_data
+#[test]
+fn test_entity_ids_fit_user_data() {
+    use crate::physics::rapier_common::{unpack_id, UserData};
+    use crate::spacetime_common::sequence::entity_id_from;
+
+    let max = entity_id_from(u32::MAX as u64).unwrap();
+    let packed = UserData {
+        body_type: 0, object_function: 0, flag: false, raw_id: max, hit_count: 0, modifier: 0, block: false, tick_count: 0,
+    }.pack();
+    assert_eq!(unpack_id(packed), u32::MAX);
+    assert_eq!(entity_id_from(1), Ok(1));
+    assert!(entity_id_from(u32::MAX as u64 + 1).is_err());
+}
+
+/// Real time accumulates across ticks into fixed sub-steps, capped on overrun
+#[test]
+fn test_substep_accumulation() {
+    use crate::physics::physics_tick::{take_substeps, MAX_SUBSTEPS_PER_TICK};
+
+    let dt = 1.0 / 60.0;
+    // 100 ms ticks: the remainder carries over, so ten ticks simulate a second
+    let mut accumulator = 0.0;
+    let mut total = 0;
+    for _ in 0..10 {
+        accumulator += 0.1;
+        let (substeps, dropped) = take_substeps(&mut accumulator, dt);
+        assert!((5..=7).contains(&substeps));
+        assert_eq!(dropped, 0.0);
+        assert!(accumulator < dt);
+        total += substeps;
+    }
+    assert!((59..=60).contains(&total), "{} sub-steps for one second", total);
+
+    // less than one step waits for the next tick
+    let mut accumulator = dt * 0.5;
+    assert_eq!(take_substeps(&mut accumulator, dt), (0, 0.0));
+    assert_eq!(accumulator, dt * 0.5);
+
+    // a long stall runs the cap and drops the whole-step backlog
+    let mut accumulator = 1.0 + dt * 0.25;
+    let (substeps, dropped) = take_substeps(&mut accumulator, dt);
+    assert_eq!(substeps, MAX_SUBSTEPS_PER_TICK);
+    assert!(dropped > 0.7 && accumulator < dt);
+}
+
+/// Impulses, forces and velocities reach dynamic bodies only; tick forces are cleared after use
+#[test]
+fn test_motion_on_dynamic_bodies() {
+    use rapier3d::prelude::*;
+    use crate::physics::motion::*;
+    use crate::physics::{step_world, PhysicsContext};
+
+    let mut world = PhysicsContext::default();
+    let crate_body = world.bodies.insert(RigidBodyBuilder::dynamic().gravity_scale(0.0).additional_mass(2.0).build());
+    let platform = world.bodies.insert(RigidBodyBuilder::kinematic_position_based().build());
+    world.id_to_body.insert(1, crate_body);
+    world.id_to_body.insert(2, platform);
+    // the first step computes mass properties
+    step_world(&mut world, &());
+
+    apply_impulse_to(&mut world, 1, vector![1.0, 0.0, 0.0]).unwrap();
+    assert!((world.bodies[crate_body].linvel().x - 0.5).abs() < 1.0e-5, "impulse / mass");
+    assert!(apply_impulse_to(&mut world, 2, vector![1.0, 0.0, 0.0]).is_err());
+    assert!(apply_torque_impulse_to(&mut world, 3, vector![1.0, 0.0, 0.0]).is_err());
+
+    set_velocity_of(&mut world, 1, vector![0.0, 0.0, 0.0], vector![0.0, 1.0, 0.0]).unwrap();
+    assert_eq!(*world.bodies[crate_body].linvel(), Vector::zeros());
+    assert_eq!(*world.bodies[crate_body].angvel(), vector![0.0, 1.0, 0.0]);
+    assert!(set_velocity_of(&mut world, 2, Vector::zeros(), Vector::zeros()).is_err());
+
+    add_force_to(&mut world, 1, vector![0.0, 0.0, 4.0]).unwrap();
+    assert_eq!(world.forced_bodies, vec![crate_body]);
+    assert_eq!(world.bodies[crate_body].user_force(), vector![0.0, 0.0, 4.0]);
+    step_world(&mut world, &());
+    assert!(world.bodies[crate_body].linvel().z > 0.0);
+    reset_tick_forces(&mut world);
+    assert!(world.forced_bodies.is_empty());
+    assert_eq!(world.bodies[crate_body].user_force(), Vector::zeros());
+}
+
+/// Projectiles are spent by impact behaviour or hit budget and expire by age or range
+#[test]
+fn test_projectile_lifecycle_rules() {
+    use rapier3d::prelude::*;
+    use spacetimedb::{Identity, Timestamp};
+    use crate::physics::projectile::{configure_collider, expiry, is_spent};
+    use crate::physics::PhysicsContext;
+    use crate::tables::projectile::{ImpactBehavior, Projectile, ProjectileSpec};
+
+    let pierce = ProjectileSpec { max_lifetime_ms: 1_000, max_hits: 3, max_distance: 50.0, on_impact: ImpactBehavior::Pierce };
+    let despawn = ProjectileSpec { on_impact: ImpactBehavior::Despawn, ..pierce };
+    assert!(is_spent(&despawn, 1));
+    assert!(!is_spent(&pierce, 2));
+    assert!(is_spent(&pierce, 3));
+
+    let p = Projectile {
+        entity_id: 1,
+        owner_id: Identity::ZERO,
+        spawned_at: Timestamp::from_micros_since_unix_epoch(0),
+        origin_x: 0.0, origin_y: 1.0, origin_z: 0.0,
+        spec: pierce,
+        hits: 0,
+    };
+    assert_eq!(expiry(&p, 500_000, &vector![10.0, 1.0, 0.0]), None);
+    assert_eq!(expiry(&p, 1_000_000, &vector![10.0, 1.0, 0.0]), Some((1_000, 10.0)));
+    assert_eq!(expiry(&p, 500_000, &vector![0.0, 1.0, 50.0]), Some((500, 50.0)));
+
+    // piercing colliders become sensors, bouncing ones fully elastic
+    let mut world = PhysicsContext::default();
+    for (id, behavior) in [(1, ImpactBehavior::Pierce), (2, ImpactBehavior::Bounce)] {
+        let body = world.bodies.insert(RigidBodyBuilder::dynamic().build());
+        world.colliders.insert_with_parent(ColliderBuilder::ball(0.1).build(), body, &mut world.bodies);
+        world.id_to_body.insert(id, body);
+        configure_collider(&mut world, id, behavior);
+    }
+    let collider = |world: &PhysicsContext, id: u32| world.bodies[world.id_to_body[&id]].colliders()[0];
+    assert!(world.colliders[collider(&world, 1)].is_sensor());
+    assert_eq!(world.colliders[collider(&world, 2)].restitution(), 1.0);
+}
+
+/// Query filters skip the caster, sensors and colliders outside the requested groups
+#[test]
+fn test_query_filtering() {
+    use rapier3d::prelude::*;
+    use crate::physics::PhysicsContext;
+    use crate::physics::queries::{line_of_sight, overlap_shape, raycast, QueryOptions};
+    use crate::physics::rapier_common::pack_id;
+
+    let mut world = PhysicsContext::default();
+    let mut add = |id: u32, x: Real, collider: ColliderBuilder| {
+        let body = world.bodies.insert(RigidBodyBuilder::fixed().translation(vector![x, 0.0, 0.0]).build());
+        world.colliders.insert_with_parent(collider.user_data(pack_id(id)).build(), body, &mut world.bodies);
+        world.id_to_body.insert(id, body);
+    };
+    add(1, 0.0, ColliderBuilder::ball(0.5));
+    add(2, 3.0, ColliderBuilder::ball(0.5).sensor(true));
+    add(3, 6.0, ColliderBuilder::cuboid(0.5, 2.0, 2.0).collision_groups(InteractionGroups::new(Group::GROUP_2, Group::ALL)));
+    add(4, 9.0, ColliderBuilder::ball(0.5).collision_groups(InteractionGroups::new(Group::GROUP_1, Group::ALL)));
+    world.query_pipeline.update(&world.colliders);
+
+    let caster = QueryOptions { exclude_entity: Some(1), ..QueryOptions::default() };
+    let solid = QueryOptions { exclude_sensors: true, ..caster };
+    let group_1 = QueryOptions { groups: InteractionGroups::new(Group::ALL, Group::GROUP_1), ..solid };
+    let first = |opts: &QueryOptions| raycast(&world, point![0.0, 0.0, 0.0], vector![1.0, 0.0, 0.0], 20.0, opts).map(|h| h.entity_id);
+    assert_eq!(first(&QueryOptions::default()), Some(1), "the ray starts inside the caster");
+    assert_eq!(first(&caster), Some(2));
+    assert_eq!(first(&solid), Some(3));
+    assert_eq!(first(&group_1), Some(4));
+
+    let near: Vec<u32> = overlap_shape(&world, &Ball::new(4.0), point![0.0, 0.0, 0.0], &caster).iter().map(|h| h.entity_id).collect();
+    assert_eq!(near, vec![2]);
+    assert!(overlap_shape(&world, &Ball::new(4.0), point![0.0, 0.0, 0.0], &solid).is_empty());
+
+    // the wall blocks sight of the target unless it is filtered out
+    assert!(!line_of_sight(&world, point![0.0, 0.0, 0.0], point![9.0, 0.0, 0.0], 4, &caster));
+    assert!(line_of_sight(&world, point![0.0, 0.0, 0.0], point![9.0, 0.0, 0.0], 4, &group_1));
+}
+
+/// Melee and AOE reach only what is inside the skill radius and in sight of its centre
+#[test]
+fn test_combat_targets_in_sphere() {
+    use rapier3d::prelude::*;
+    use crate::physics::PhysicsContext;
+    use crate::physics::queries::QueryOptions;
+    use crate::physics::rapier_common::pack_id;
+    use crate::reducers::combat::{targets_in_sphere, AOE, MELEE};
+
+    let mut world = PhysicsContext::default();
+    let mut add = |id: u32, x: Real, collider: ColliderBuilder| {
+        let body = world.bodies.insert(RigidBodyBuilder::fixed().translation(vector![x, 0.0, 0.0]).build());
+        world.colliders.insert_with_parent(collider.user_data(pack_id(id)).build(), body, &mut world.bodies);
+        world.id_to_body.insert(id, body);
+    };
+    add(1, 0.0, ColliderBuilder::ball(0.5)); // caster
+    add(2, 1.5, ColliderBuilder::ball(0.5));
+    add(3, -3.0, ColliderBuilder::ball(0.5)); // behind the wall
+    add(4, -2.0, ColliderBuilder::cuboid(0.1, 2.0, 2.0));
+    add(5, 10.0, ColliderBuilder::ball(0.5)); // out of reach
+    world.query_pipeline.update(&world.colliders);
+
+    let opts = QueryOptions { exclude_entity: Some(1), exclude_sensors: true, ..QueryOptions::default() };
+    let origin = point![0.0, 0.0, 0.0];
+    assert_eq!(targets_in_sphere(&world, &MELEE, origin, Some(2), &opts), vec![2]);
+    assert!(targets_in_sphere(&world, &MELEE, origin, Some(5), &opts).is_empty());
+    assert!(targets_in_sphere(&world, &AOE, origin, Some(3), &opts).is_empty(), "blocked by the wall");
+
+    let mut hit = targets_in_sphere(&world, &AOE, origin, None, &opts);
+    hit.sort();
+    assert_eq!(hit, vec![2, 4]);
+}
+
+/// Primitive and asset-backed shapes build the matching Rapier shapes; bad assets are refused
+#[test]
+fn test_shapes_and_assets_build() {
+    use rapier3d::prelude::*;
+    use crate::spacetime_common::shape::{AxialSize, ColliderShape, Vec3};
+    use crate::tables::collider_asset::{ColliderAssetData, HeightFieldData, MeshData};
+
+    let build = |shape: ColliderShape, asset: Option<&ColliderAssetData>| {
+        shape.to_rapier(asset, false, InteractionGroups::all()).map(|b| b.build())
+    };
+    let axial = AxialSize { half_height: 0.9, radius: 0.3 };
+    let capsule = build(ColliderShape::Capsule(axial), None).unwrap();
+    assert_eq!(capsule.shape().as_capsule().map(|c| (c.half_height(), c.radius)), Some((0.9, 0.3)));
+    let cylinder = build(ColliderShape::Cylinder(axial), None).unwrap();
+    assert_eq!(cylinder.shape().as_cylinder().map(|c| (c.half_height, c.radius)), Some((0.9, 0.3)));
+    let cone = build(ColliderShape::Cone(axial), None).unwrap();
+    assert_eq!(cone.shape().as_cone().map(|c| (c.half_height, c.radius)), Some((0.9, 0.3)));
+
+    let corners: Vec<Vec3> = (0..8).map(|i| Vec3::new((i & 1) as f32, ((i >> 1) & 1) as f32, ((i >> 2) & 1) as f32)).collect();
+    let hull = ColliderAssetData::ConvexHull(corners.clone());
+    assert!(hull.validate().is_ok());
+    assert_eq!(build(ColliderShape::ConvexHull(1), Some(&hull)).unwrap().shape().shape_type(), ShapeType::ConvexPolyhedron);
+
+    let mesh = ColliderAssetData::TriMesh(MeshData { vertices: corners[..3].to_vec(), indices: vec![0, 1, 2] });
+    assert!(mesh.validate().is_ok());
+    assert_eq!(build(ColliderShape::TriMesh(2), Some(&mesh)).unwrap().shape().shape_type(), ShapeType::TriMesh);
+
+    let field = ColliderAssetData::HeightField(HeightFieldData { nrows: 2, ncols: 3, heights: vec![0.0; 6], scale: Vec3::new(10.0, 1.0, 10.0) });
+    assert!(field.validate().is_ok());
+    assert_eq!(build(ColliderShape::HeightField(3), Some(&field)).unwrap().shape().shape_type(), ShapeType::HeightField);
+
+    // the asset must exist and match the shape kind
+    assert!(build(ColliderShape::TriMesh(2), None).is_err());
+    assert!(build(ColliderShape::TriMesh(2), Some(&hull)).is_err());
+    // malformed geometry never reaches the table
+    assert!(ColliderAssetData::ConvexHull(corners[..3].to_vec()).validate().is_err());
+    assert!(ColliderAssetData::TriMesh(MeshData { vertices: corners[..3].to_vec(), indices: vec![0, 1, 5] }).validate().is_err());
+}
+
+/// Every child collider of a compound body is placed at its offset and resolves to the body
+#[test]
+fn test_compound_body_colliders() {
+    use rapier3d::prelude::*;
+    use spacetimedb::Identity;
+    use crate::physics::assets::BodyResources;
+    use crate::physics::rapier_common::{get_object_function, unpack_id};
+    use crate::physics::spawn::{insert_body, new_body_row, MAX_COLLIDERS_PER_BODY};
+    use crate::tables::physics_body::PhysicsBody;
+    use crate::physics::PhysicsContext;
+    use crate::spacetime_common::collision::{object_function, DYNAMIC_BODY_TYPE};
+    use crate::spacetime_common::shape::{ColliderShape, ColliderSpec, Vec3};
+
+    let hull = ColliderSpec::new(ColliderShape::Cuboid(Vec3::new(2.0, 1.0, 4.0)));
+    let wheel = |x: f32| ColliderSpec { offset: Vec3::new(x, -0.5, 1.5), ..ColliderSpec::new(ColliderShape::Sphere(0.4)) };
+    let aura = ColliderSpec { sensor: true, object_function: object_function::AURA, ..ColliderSpec::new(ColliderShape::Sphere(3.0)) };
+    let mut row = new_body_row(Identity::ZERO, 0, 0.0, 2.0, 0.0, vec![hull, wheel(-1.0), wheel(1.0), aura], DYNAMIC_BODY_TYPE);
+    row.entity_id = 9;
+
+    let mut world = PhysicsContext::default();
+    let handle = insert_body(&mut world, &row, &BodyResources::default()).unwrap();
+    let colliders = world.bodies[handle].colliders().to_vec();
+    assert_eq!(colliders.len(), 4);
+    assert!(colliders.iter().all(|c| unpack_id(world.colliders[*c].user_data) == 9));
+    assert_eq!(*world.colliders[colliders[1]].position_wrt_parent().unwrap(), Isometry::translation(-1.0, -0.5, 1.5));
+    assert_eq!(world.colliders[colliders[1]].position().translation.vector, vector![-1.0, 1.5, 1.5]);
+    let sensor = &world.colliders[colliders[3]];
+    assert!(sensor.is_sensor());
+    assert_eq!(get_object_function(sensor.user_data), object_function::AURA);
+
+    // too many, none, or one bad child: nothing is inserted
+    for colliders in [
+        vec![wheel(0.0); MAX_COLLIDERS_PER_BODY + 1],
+        vec![],
+        vec![wheel(0.0), ColliderSpec::new(ColliderShape::Sphere(-1.0))],
+    ] {
+        let bad = PhysicsBody { entity_id: 10, colliders, ..row.clone() };
+        assert!(insert_body(&mut world, &bad, &BodyResources::default()).is_err());
+    }
+    assert_eq!((world.bodies.len(), world.colliders.len()), (1, 4));
+    assert!(!world.id_to_body.contains_key(&10));
+}
+
+/// A body's material sets its damping and collider surfaces unless a collider names its own
+#[test]
+fn test_materials_apply_to_bodies_and_colliders() {
+    use spacetimedb::Identity;
+    use crate::physics::assets::BodyResources;
+    use crate::physics::spawn::{insert_body, new_body_row};
+    use crate::physics::PhysicsContext;
+    use crate::spacetime_common::collision::DYNAMIC_BODY_TYPE;
+    use crate::spacetime_common::shape::{ColliderShape, ColliderSpec};
+    use crate::tables::physics_material::PhysicsMaterial;
+
+    let presets = PhysicsMaterial::presets();
+    assert!(presets.iter().all(|m| m.validate().is_ok()));
+    let ice = presets.iter().find(|m| m.name == "ice").unwrap().clone();
+    let rubber = presets.iter().find(|m| m.name == "rubber").unwrap().clone();
+    for bad in [
+        PhysicsMaterial { friction: -0.1, ..ice.clone() },
+        PhysicsMaterial { density: 0.0, ..ice.clone() },
+        PhysicsMaterial { gravity_scale: f32::NAN, ..ice.clone() },
+        PhysicsMaterial { impact_threshold: Some(-1.0), ..ice.clone() },
+        PhysicsMaterial { name: " ".into(), ..ice.clone() },
+    ] {
+        assert!(bad.validate().is_err(), "{:?} should be rejected", bad);
+    }
+
+    let res = BodyResources {
+        materials: presets.iter().map(|m| (m.name.clone(), m.clone())).collect(),
+        ..BodyResources::default()
+    };
+    let bumper = ColliderSpec { material: Some("rubber".into()), ..ColliderSpec::new(ColliderShape::Sphere(0.5)) };
+    let mut row = new_body_row(Identity::ZERO, 0, 0.0, 0.0, 0.0, vec![ColliderSpec::new(ColliderShape::Sphere(0.5)), bumper], DYNAMIC_BODY_TYPE);
+    row.entity_id = 1;
+    row.material = Some("ice".into());
+
+    let mut world = PhysicsContext::default();
+    let handle = insert_body(&mut world, &row, &res).unwrap();
+    let body = &world.bodies[handle];
+    assert_eq!(body.angular_damping(), ice.angular_damping);
+    let (plain, bumper) = (&world.colliders[body.colliders()[0]], &world.colliders[body.colliders()[1]]);
+    assert_eq!((plain.friction(), plain.restitution()), (ice.friction, ice.restitution));
+    assert_eq!((bumper.friction(), bumper.restitution()), (rubber.friction, rubber.restitution));
+
+    // naming a material that doesn't exist fails the whole body
+    row.material = Some("mud".into());
+    assert!(insert_body(&mut world, &row, &res).is_err());
+}
+
+/// Events past the per-tick budget are deferred in order, never dropped
+#[test]
+fn test_event_budget_defers_in_order() {
+    use std::collections::VecDeque;
+    use crossbeam::channel::unbounded;
+    use rapier3d::prelude::*;
+    use crate::physics::take_collision_events;
+
+    let event = |i: u32| CollisionEvent::Started(ColliderHandle::from_raw_parts(i, 0), ColliderHandle::from_raw_parts(100, 0), CollisionEventFlags::empty());
+    let first = |events: &[CollisionEvent]| events.iter().map(|e| e.collider1().into_raw_parts().0).collect::<Vec<_>>();
+    let (tx, rx) = unbounded();
+    let mut pending = VecDeque::new();
+
+    (0..5).for_each(|i| tx.send(event(i)).unwrap());
+    assert_eq!(first(&take_collision_events(&rx, &mut pending, 3)), vec![0, 1, 2]);
+    assert_eq!(pending.len(), 2);
+
+    // the backlog goes first, then this tick's events
+    (5..7).for_each(|i| tx.send(event(i)).unwrap());
+    assert_eq!(first(&take_collision_events(&rx, &mut pending, 3)), vec![3, 4, 5]);
+    assert_eq!(first(&take_collision_events(&rx, &mut pending, 3)), vec![6]);
+    assert!(pending.is_empty());
+}
+
+/// Removing an entity from its world leaves nothing keyed by its ID or handles behind
+#[test]
+fn test_remove_from_world_clears_entity_state() {
+    use rapier3d::prelude::*;
+    use spacetimedb::Timestamp;
+    use crate::physics::character::CharacterState;
+    use crate::physics::rewind::record_history;
+    use crate::physics::spawn::remove_from_world;
+    use crate::physics::PhysicsContext;
+
+    let mut world = PhysicsContext::default();
+    let add = |world: &mut PhysicsContext, id: u32| {
+        let body = world.bodies.insert(RigidBodyBuilder::dynamic().build());
+        world.colliders.insert_with_parent(ColliderBuilder::ball(0.5).build(), body, &mut world.bodies);
+        world.id_to_body.insert(id, body);
+        world.last_transforms.insert(body, (Vector::zeros(), Rotation::identity()));
+        world.last_velocities.insert(body, (Vector::zeros(), Vector::zeros()));
+        world.forced_bodies.push(body);
+        world.awake_bodies.push(body);
+        world.characters.insert(id, CharacterState::default());
+        world.pending_damage.insert(id, 5);
+        world.invulnerable_until.insert(id, Timestamp::from_micros_since_unix_epoch(0));
+        body
+    };
+    let gone = add(&mut world, 1);
+    let kept = add(&mut world, 2);
+    record_history(&mut world, Timestamp::from_micros_since_unix_epoch(0));
+
+    remove_from_world(&mut world, 1);
+    assert!(world.bodies.get(gone).is_none());
+    assert_eq!((world.bodies.len(), world.colliders.len()), (1, 1));
+    assert!(!world.id_to_body.contains_key(&1) && !world.characters.contains_key(&1));
+    assert!(!world.pending_damage.contains_key(&1) && !world.invulnerable_until.contains_key(&1));
+    assert!(!world.last_transforms.contains_key(&gone) && !world.last_velocities.contains_key(&gone));
+    assert_eq!((world.forced_bodies.clone(), world.awake_bodies.clone()), (vec![kept], vec![kept]));
+    let kept_collider = world.bodies[kept].colliders()[0];
+    assert!(world.history.iter().all(|s| s.poses.iter().all(|(h, _)| *h == kept_collider)));
+
+    // removing an unknown entity is a no-op
+    remove_from_world(&mut world, 1);
+    assert_eq!(world.bodies.len(), 1);
+}
+
+/// Input sequence numbers order correctly across the u32 wrap-around
+#[test]
+fn test_input_sequence_wraps() {
+    use crate::reducers::world::seq_newer;
+
+    assert!(seq_newer(2, 1));
+    assert!(!seq_newer(1, 1), "a repeated input is stale");
+    assert!(!seq_newer(1, 2));
+    assert!(seq_newer(0, u32::MAX));
+    assert!(seq_newer(5, u32::MAX - 5));
+    assert!(!seq_newer(u32::MAX, 0));
+}
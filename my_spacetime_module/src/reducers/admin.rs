@@ -0,0 +1,99 @@
+use spacetimedb::{Identity, ReducerContext, Table};
+use crate::tables::user_role::{user_role, Role, UserRole};
+use crate::reducers::auth::require_admin;
+use crate::tables::region_config::{region_config, RegionConfig};
+use crate::tables::physics_material::{physics_material, PhysicsMaterial};
+use crate::tables::physics_body::physics_body;
+use crate::physics::rewind::MAX_REWIND_LIMIT_MICROS;
+use crate::physics::handoff::DEFAULT_REGION;
+
+#[spacetimedb::reducer]
+/// Set another identity's role; `Role::Player` removes its row
+pub fn set_role(ctx: &ReducerContext, identity: Identity, role: Role) -> Result<(), String> {
+    require_admin(ctx)?;
+    if identity == ctx.sender && role != Role::Admin {
+        return Err("Admins can't demote themselves".into());
+    }
+    ctx.db.user_role().identity().delete(identity);
+    if role != Role::Player {
+        ctx.db.user_role().insert(UserRole { identity, role, granted_by: ctx.sender, granted_at: ctx.timestamp });
+    }
+    log::info!("{} set role of {} to {:?}", ctx.sender, identity, role);
+    Ok(())
+}
+
+#[spacetimedb::reducer]
+/// Create or replace the physics configuration of a region; applied on its next physics tick.
+/// Every region but the default one (which owns unclaimed chunks) needs a chunk range of its
+/// own that doesn't overlap another region's.
+pub fn set_region_config(ctx: &ReducerContext, config: RegionConfig) -> Result<(), String> {
+    require_admin(ctx)?;
+    if config.tick_interval_micros < 1_000 {
+        return Err("Tick interval must be at least 1ms".into());
+    }
+    if config.solver_iterations == 0 {
+        return Err("Solver iterations must be at least 1".into());
+    }
+    if config.max_collision_events == 0 {
+        return Err("Collision event budget must be at least 1".into());
+    }
+    if !(0..=MAX_REWIND_LIMIT_MICROS).contains(&config.max_rewind_micros) {
+        return Err(format!("Rewind window must be between 0 and {}µs", MAX_REWIND_LIMIT_MICROS));
+    }
+    if ![config.gravity_x, config.gravity_y, config.gravity_z].iter().all(|g| g.is_finite()) {
+        return Err("Gravity must be finite".into());
+    }
+    if !config.min_ccd_dt.is_finite() || config.min_ccd_dt <= 0.0 {
+        return Err("min_ccd_dt must be positive".into());
+    }
+    if !config.has_chunks() && config.region != DEFAULT_REGION {
+        return Err("Region chunk range is required (min <= max on both axes)".into());
+    }
+    if let Some(other) = ctx.db.region_config().iter().find(|c| c.region != config.region && ranges_overlap(c, &config)) {
+        return Err(format!("Region chunk range overlaps region {}", other.region));
+    }
+
+    log::info!("Region {} config updated: {:?}", config.region, config);
+    if ctx.db.region_config().region().find(config.region).is_some() {
+        ctx.db.region_config().region().update(config);
+    } else {
+        ctx.db.region_config().insert(config);
+    }
+    Ok(())
+}
+
+#[spacetimedb::reducer]
+/// Create or replace a named physics material; bodies pick it up when spawned or hydrated
+pub fn set_physics_material(ctx: &ReducerContext, material: PhysicsMaterial) -> Result<(), String> {
+    require_admin(ctx)?;
+    material.validate()?;
+
+    log::info!("Physics material '{}' updated: {:?}", material.name, material);
+    if ctx.db.physics_material().name().find(&material.name).is_some() {
+        ctx.db.physics_material().name().update(material);
+    } else {
+        ctx.db.physics_material().insert(material);
+    }
+    Ok(())
+}
+
+#[spacetimedb::reducer]
+/// Delete a physics material no body or collider refers to any more
+pub fn delete_physics_material(ctx: &ReducerContext, name: String) -> Result<(), String> {
+    require_admin(ctx)?;
+    let uses = |m: &Option<String>| m.as_ref() == Some(&name);
+    if ctx.db.physics_body().iter().any(|b| uses(&b.material) || b.colliders.iter().any(|c| uses(&c.material))) {
+        return Err(format!("Physics material '{}' is still in use", name));
+    }
+    if !ctx.db.physics_material().name().delete(&name) {
+        return Err("Physics material not found".into());
+    }
+    Ok(())
+}
+
+/// True if two regions' chunk ranges share at least one chunk
+fn ranges_overlap(a: &RegionConfig, b: &RegionConfig) -> bool {
+    a.has_chunks() && b.has_chunks()
+        && a.min_chunk_x <= b.max_chunk_x && b.min_chunk_x <= a.max_chunk_x
+        && a.min_chunk_y <= b.max_chunk_y && b.min_chunk_y <= a.max_chunk_y
+}
@@ -0,0 +1,144 @@
+//! Roles and the guards reducers use to enforce them.
+//!
+//! Roles live in the `user_role` table (identities without a row are players); the module
+//! itself (scheduled and lifecycle reducers acting as the database identity) counts as admin.
+//! Spawning and despawning are checked per body type by `can_spawn` / `can_despawn`; callers
+//! below Builder are further limited by `check_player_spawn`.
+use spacetimedb::{Identity, ReducerContext};
+use crate::physics::queries::caster_entity;
+use crate::spacetime_common::collision::*;
+use crate::spacetime_common::shape::ColliderSpec;
+use crate::tables::physics_body::{physics_body, PhysicsBody};
+use crate::tables::user_role::{user_role, Role};
+
+/// Most colliders a body spawned below Builder may have
+pub const PLAYER_MAX_COLLIDERS: usize = 1;
+/// Largest bounding radius (m) of a collider spawned below Builder, offset included
+pub const PLAYER_MAX_COLLIDER_RADIUS: f32 = 1.0;
+/// How far (m) from the caller's own body a spawn below Builder may be placed
+pub const PLAYER_SPAWN_RANGE: f32 = 5.0;
+/// Fastest linear speed (m/s) a caller below Builder may give a body
+pub const PLAYER_MAX_SPEED: f32 = 30.0;
+/// Fastest angular speed (rad/s) a caller below Builder may give a body
+pub const PLAYER_MAX_ANGULAR_SPEED: f32 = 20.0;
+/// Largest linear or angular impulse (N·s) a caller below Builder may apply at once
+pub const PLAYER_MAX_IMPULSE: f32 = 20.0;
+/// Largest force (N) a caller below Builder may apply for a tick
+pub const PLAYER_MAX_FORCE: f32 = 200.0;
+
+fn norm(x: f32, y: f32, z: f32) -> f32 {
+    (x * x + y * y + z * z).sqrt()
+}
+
+/// The caller's role
+pub fn role_of(ctx: &ReducerContext, identity: Identity) -> Role {
+    if identity == ctx.identity() {
+        return Role::Admin;
+    }
+    ctx.db.user_role().identity().find(identity).map_or(Role::Player, |r| r.role)
+}
+
+/// Reject the call unless the sender has at least `role`
+pub fn require_role(ctx: &ReducerContext, role: Role) -> Result<(), String> {
+    if role_of(ctx, ctx.sender) >= role {
+        Ok(())
+    } else {
+        Err(format!("Unauthorized: {:?} only", role))
+    }
+}
+
+/// Reject the call unless the sender is an admin (or the module itself)
+pub fn require_admin(ctx: &ReducerContext) -> Result<(), String> {
+    require_role(ctx, Role::Admin)
+}
+
+/// Lowest role allowed to spawn a body type through a reducer; None for bodies only the
+/// module creates (player bodies come from `on_client_connected`)
+pub fn spawn_role(body_type: u8) -> Option<Role> {
+    match body_type {
+        PROJECTILE_BODY_TYPE => Some(Role::Player),
+        DYNAMIC_BODY_TYPE | KINEMATIC_BODY_TYPE => Some(Role::Builder),
+        STATIC_BODY_TYPE => Some(Role::Admin),
+        _ => None,
+    }
+}
+
+/// Check the sender may spawn a body of this type; spawned bodies are always owned by the sender
+pub fn can_spawn(ctx: &ReducerContext, body_type: u8) -> Result<(), String> {
+    let role = spawn_role(body_type).ok_or_else(|| format!("Body type {} can't be spawned by clients", body_type))?;
+    require_role(ctx, role)
+}
+
+/// A collider a caller below Builder may spawn: a small plain shape with no gameplay role
+/// (`object_function`, ghosting `flag` / `modifier`), group override, contact-force
+/// reporting (impact damage) or asset geometry
+pub fn player_collider_allowed(spec: &ColliderSpec) -> Result<(), String> {
+    if spec.object_function != object_function::NONE || spec.flag || spec.modifier != 0 {
+        return Err("Only builders may spawn colliders with an object function, flag or modifier".into());
+    }
+    if spec.groups.is_some() {
+        return Err("Only builders may override collision groups".into());
+    }
+    if spec.contact_force_threshold.is_some() {
+        return Err("Only builders may spawn colliders that deal impact damage".into());
+    }
+    let offset = norm(spec.offset.x, spec.offset.y, spec.offset.z);
+    let radius = spec.shape.bounding_radius().ok_or("Only builders may spawn asset-backed colliders")?;
+    if radius + offset > PLAYER_MAX_COLLIDER_RADIUS {
+        return Err(format!("Collider too large (max {} m)", PLAYER_MAX_COLLIDER_RADIUS));
+    }
+    Ok(())
+}
+
+/// Limits on what callers below Builder may spawn: at most `PLAYER_MAX_COLLIDERS` colliders
+/// passing `player_collider_allowed`, within `PLAYER_SPAWN_RANGE` of their own body and in its
+/// region, moving no faster than `PLAYER_MAX_SPEED`
+pub fn check_player_spawn(ctx: &ReducerContext, row: &PhysicsBody) -> Result<(), String> {
+    if role_of(ctx, ctx.sender) >= Role::Builder {
+        return Ok(());
+    }
+    if row.colliders.len() > PLAYER_MAX_COLLIDERS {
+        return Err(format!("At most {} collider(s) per body", PLAYER_MAX_COLLIDERS));
+    }
+    row.colliders.iter().try_for_each(player_collider_allowed)?;
+    check_limit("Speed", [row.vel_x, row.vel_y, row.vel_z], PLAYER_MAX_SPEED)?;
+
+    let own = caster_entity(ctx)
+        .and_then(|id| ctx.db.physics_body().entity_id().find(id))
+        .ok_or("You have no physics body to spawn from")?;
+    if own.region != row.region {
+        return Err("Spawns must be in your own region".into());
+    }
+    if norm(row.pos_x - own.pos_x, row.pos_y - own.pos_y, row.pos_z - own.pos_z) > PLAYER_SPAWN_RANGE {
+        return Err(format!("Spawn point too far from your body (max {} m)", PLAYER_SPAWN_RANGE));
+    }
+    Ok(())
+}
+
+/// Err if `v` is longer than `max`
+pub fn check_limit(what: &str, v: [f32; 3], max: f32) -> Result<(), String> {
+    if norm(v[0], v[1], v[2]) <= max {
+        Ok(())
+    } else {
+        Err(format!("{} above the player limit of {}", what, max))
+    }
+}
+
+/// `check_limit` for callers below Builder only (speeds, impulses and forces they apply)
+pub fn check_player_limit(ctx: &ReducerContext, what: &str, v: [f32; 3], max: f32) -> Result<(), String> {
+    if role_of(ctx, ctx.sender) >= Role::Builder {
+        return Ok(());
+    }
+    check_limit(what, v, max)
+}
+
+/// Admins may despawn anything; everyone else only bodies they own and could have spawned
+pub fn can_despawn(ctx: &ReducerContext, body: &PhysicsBody) -> Result<(), String> {
+    if require_admin(ctx).is_ok() {
+        return Ok(());
+    }
+    if body.owner_id != ctx.sender {
+        return Err("You don't own this body".into());
+    }
+    can_spawn(ctx, body.body_type)
+}
@@ -1,36 +1,105 @@
-use spacetimedb::{Identity, ReducerContext, Table};
-use crate::tables::{physics_body::physics_body, player::player};
-
-/// Apply damage to a player by deleting the old record and inserting the updated one
-pub fn _apply_damage(ctx: &ReducerContext, target_id: Identity, _damage: u32) -> Result<(), String> {
-    if let Some(mut _player) = ctx.db.player().iter().find(|p| p.player_id == target_id).map(|p| p.clone()) {
-        // health moved to physics_body table, applying damage is not implemented jet
-        //player.health = player.health.saturating_sub(damage);
-        // Update player using primary key column
-        ctx.db.player().player_id().update(_player.clone());
-        Ok(())
-    } else {
-        Err("Target not found".to_string())
+use spacetimedb::{ReducerContext, Timestamp};
+use rapier3d::prelude::*;
+use crate::physics::{world_for_region, PhysicsContext, PHYSICS_CONTEXTS};
+use crate::physics::queries::{caster_entity, line_of_sight, overlap_shape, QueryOptions};
+use crate::physics::rewind::with_rewound;
+use crate::physics::skills::{apply_damage_in, start_cooldown, SkillId};
+use crate::tables::physics_body::physics_body;
+
+/// Server-side definition of a combat reducer's attack; clients only choose target or aim point
+pub struct CombatSkill {
+    /// `damage_event.skill_id` and `skill_cooldown.skill_id` (outside the skill registry range)
+    pub id: SkillId,
+    pub damage: u32,
+    pub radius: f32,
+    /// Furthest an AOE centre may be from the caster's body origin
+    pub cast_range: f32,
+    pub cooldown_ms: u32,
+}
+
+/// Single-target hit on something within `radius` of the caster's body origin
+pub const MELEE: CombatSkill = CombatSkill { id: 250, damage: 10, radius: 2.0, cast_range: 0.0, cooldown_ms: 500 };
+/// Blast around a point up to `cast_range` away from the caster
+pub const AOE: CombatSkill = CombatSkill { id: 251, damage: 25, radius: 4.0, cast_range: 15.0, cooldown_ms: 3000 };
+
+/// Caster's position and query options (caster excluded, caster's collision groups)
+fn caster(world: &PhysicsContext, entity_id: u32) -> Result<(Point<Real>, QueryOptions), String> {
+    let handle = world.id_to_body.get(&entity_id).ok_or("Caster body not loaded")?;
+    let body = &world.bodies[*handle];
+    let groups = body.colliders().first()
+        .and_then(|c| world.colliders.get(*c))
+        .map_or(InteractionGroups::all(), |c| c.collision_groups());
+    Ok((
+        Point::from(*body.translation()),
+        QueryOptions { groups, exclude_entity: Some(entity_id), exclude_sensors: true },
+    ))
+}
+
+/// Entities within `skill.radius` of `center` that can be seen from it (all, or just `only_target`)
+pub(crate) fn targets_in_sphere(
+    world: &PhysicsContext,
+    skill: &CombatSkill,
+    center: Point<Real>,
+    only_target: Option<u32>,
+    opts: &QueryOptions,
+) -> Vec<u32> {
+    overlap_shape(world, &Ball::new(skill.radius), center, opts)
+        .into_iter()
+        .filter(|hit| only_target.is_none_or(|t| t == hit.entity_id))
+        .filter(|hit| line_of_sight(world, center, hit.point, hit.entity_id, opts))
+        .map(|hit| hit.entity_id)
+        .collect()
+}
+
+/// Damage every entity within `skill.radius` of `center` (default: the caster) that can be seen
+/// from it; returns how many were hit
+fn damage_in_sphere(
+    ctx: &ReducerContext,
+    skill: &CombatSkill,
+    center: Option<Point<Real>>,
+    only_target: Option<u32>,
+    view_at: Option<Timestamp>,
+) -> Result<u32, String> {
+    let caster_id = caster_entity(ctx).ok_or("Caster has no physics body")?;
+    let region = ctx.db.physics_body().entity_id().find(caster_id).ok_or("Caster has no physics body")?.region;
+
+    let mut map = PHYSICS_CONTEXTS.lock().unwrap();
+    let world = world_for_region(ctx, &mut map, region);
+    let (caster_pos, opts) = caster(world, caster_id)?;
+    let center = center.unwrap_or(caster_pos);
+    if (center - caster_pos).norm() > skill.cast_range + Real::EPSILON {
+        return Err("Target point out of range".into());
+    }
+    start_cooldown(ctx, skill.id, skill.cooldown_ms)?;
+
+    // targets are checked where the caster saw them (lag compensation)
+    let targets = with_rewound(world, ctx.timestamp, view_at, |world| {
+        targets_in_sphere(world, skill, center, only_target, &opts)
+    });
+    for &target in &targets {
+        apply_damage_in(ctx, world, skill.id, target, skill.damage);
     }
+    Ok(targets.len() as u32)
 }
 
 #[spacetimedb::reducer]
-/// Single-target melee attack
-pub fn _combat_melee(ctx: &ReducerContext, target: Identity, damage: u32) -> Result<(), String> {
-    _apply_damage(ctx, target, damage)
+/// Single-target melee attack (see `MELEE`) on an entity within reach of the caster's body;
+/// `view_at` is the server time of the world state the client saw (see `rewind`)
+pub fn _combat_melee(ctx: &ReducerContext, target_entity: u32, view_at: Option<Timestamp>) -> Result<(), String> {
+    let hits = damage_in_sphere(ctx, &MELEE, None, Some(target_entity), view_at)?;
+    if hits == 0 {
+        return Err("Target out of reach or not visible".into());
+    }
+    Ok(())
 }
 
 #[spacetimedb::reducer]
-/// Area-of-effect damage around a point
-pub fn _combat_aoe(ctx: &ReducerContext, center_x: f32, center_y: f32, radius: f32, damage: u32) -> Result<(), String> {
-    for row in ctx.db.physics_body().iter() {
-        let p = row.clone();
-        let dx = p.pos_x - center_x;
-        let dy = p.pos_y - center_y;
-        if (dx*dx + dy*dy).sqrt() <= radius {
-            _apply_damage(ctx, p.owner_id, damage)?;
-        }
+/// Area-of-effect damage (see `AOE`) around a point within `AOE.cast_range` of the caster
+pub fn _combat_aoe(ctx: &ReducerContext, center_x: f32, center_y: f32, center_z: f32, view_at: Option<Timestamp>) -> Result<(), String> {
+    if ![center_x, center_y, center_z].iter().all(|v| v.is_finite()) {
+        return Err("Non-finite AOE center".into());
     }
-
+    let hits = damage_in_sphere(ctx, &AOE, Some(point![center_x, center_y, center_z]), None, view_at)?;
+    log::debug!("AOE hit {} entities", hits);
     Ok(())
-}
\ No newline at end of file
+}
@@ -5,15 +5,23 @@ use crate::tables::physics_body::physics_body;
 use crate::tables::player::{Player, PlayerStatus};
 use crate::tables::scheduling::PhysicsTickSchedule;
 use crate::tables::game_item::GameItem;
-use crate::physics::spawn_rigid_body;
+use crate::physics::{spawn_rigid_body, load_region_config};
+use crate::physics::spawn::{new_body_row, spawn_body};
+use crate::physics::dormancy::wake_player_region;
+use crate::tables::user_role::{user_role, Role, UserRole};
+use crate::tables::physics_material::{physics_material, PhysicsMaterial};
 use crate::tables::game_item::game_item;
 use crate::tables::scheduling::physics_tick_schedule;
 use crate::tables::player::player;
+use crate::tables::player_input::player_input;
 use crate::spacetime_common::spatial::calculate_chunk;
-use crate::spacetime_common::collision::STATIC_BODY_TYPE;
+use crate::spacetime_common::shape::{ColliderShape, ColliderSpec, Vec3};
+use crate::spacetime_common::collision::{STATIC_BODY_TYPE, PLAYER_BODY_TYPE};
 use crate::tables::buff_expiry_schedule::BuffExpirySchedule;
 use crate::tables::buff_expiry_schedule::buff_expiry_schedule;
 use crate::tables::damage_event::damage_event;
+use crate::tables::query_hit::query_hit;
+use crate::tables::despawn_event::despawn_event;
 /**
  * Initialization reducer called when the module is first published.
  * 
@@ -22,6 +30,14 @@ use crate::tables::damage_event::damage_event;
 #[spacetimedb::reducer(init)]
 pub fn module_init(ctx: &ReducerContext) -> Result<(), String> {
     log::info!("Game module initialized");
+
+    // The publisher becomes the first admin (can change region configs and set roles)
+    ctx.db.user_role().insert(UserRole { identity: ctx.sender, role: Role::Admin, granted_by: ctx.sender, granted_at: ctx.timestamp });
+
+    // Reusable materials ("ice", "rubber", "metal") for spawn_rigid_body
+    for material in PhysicsMaterial::presets() {
+        ctx.db.physics_material().insert(material);
+    }
     
     // Schedule physics ticks to run every 100ms (10 times per second)
     schedule_physics_tick(ctx, 0, None)?;
@@ -37,7 +53,7 @@ pub fn module_init(ctx: &ReducerContext) -> Result<(), String> {
         item_type: "Consumable".to_string(),
         value: 25,
         position_x: Some(65.0),
-        position_y: Some(65.0),
+        position_z: Some(65.0),
         chunk_x: Some(calculate_chunk(65.0)),
         chunk_y: Some(calculate_chunk(65.0)),
         is_dropped: true,
@@ -47,15 +63,17 @@ pub fn module_init(ctx: &ReducerContext) -> Result<(), String> {
     // Insert into database - pass the struct directly, not a reference
     ctx.db.game_item().insert(health_potion);
 
-    // Spawn a static ground collider
+    // Spawn a static ground collider: the x/z ground plane, top face at y = 0
     spawn_rigid_body(
         ctx,
         0, // physics region
         50.0,
+        -0.05,
         50.0,
-        -1.0,
-        format!("Box({}, {}, {})", 1000, 0.1, 1000),
+        ColliderSpec::new(ColliderShape::Cuboid(Vec3::new(1000.0, 0.1, 1000.0))),
         STATIC_BODY_TYPE,
+        0.0, 0.0, 0.0,
+        None,
     )?;
 
     // Schedule buff expiry every second
@@ -67,22 +85,14 @@ pub fn module_init(ctx: &ReducerContext) -> Result<(), String> {
     Ok(())
 }
 
+/// Default interval between scheduled physics ticks (100ms, 10 ticks per second);
+/// overridden per region by `RegionConfig::tick_interval_micros`
+pub const PHYSICS_TICK_INTERVAL_MICROS: i64 = 100_000;
+
 /**
  * Helper function to schedule the next physics tick
  */
 pub fn schedule_physics_tick(ctx: &ReducerContext, region: u32, last_id: Option<u64>) -> Result<(), String> {
-    // Get the next ID
-    let next_id = if let Some(id) = last_id {
-        id + 1
-    } else {
-        // If we don't have a previous ID, find the max ID and increment
-        let max_id = ctx.db.physics_tick_schedule().iter()
-            .map(|s| s.scheduled_id)
-            .max()
-            .unwrap_or(0);
-        max_id + 1
-    };
-    
     // Determine the base time for the next tick:
     // - if we have a prior schedule with the ID we're incrementing, use its scheduled_at time
     // - otherwise fall back to "now"
@@ -103,13 +113,14 @@ pub fn schedule_physics_tick(ctx: &ReducerContext, region: u32, last_id: Option<
         ctx.timestamp
     };
     
-    // Add a fixed 100ms (100,000 μs) to the base time
-    let next_micros = base_time.to_micros_since_unix_epoch() + 100_000;
+    // Add the region's configured tick interval to the base time
+    let interval = load_region_config(ctx, region).tick_interval_micros;
+    let next_micros = base_time.to_micros_since_unix_epoch() + interval;
     let next_time = Timestamp::from_micros_since_unix_epoch(next_micros);
     
     // Create the schedule entry
     let schedule = PhysicsTickSchedule {
-        scheduled_id: next_id,
+        scheduled_id: 0, // auto_inc: regions tick independently, so IDs can't be derived from last_id
         scheduled_at: ScheduleAt::Time(next_time),
         region,
     };
@@ -120,6 +131,33 @@ pub fn schedule_physics_tick(ctx: &ReducerContext, region: u32, last_id: Option<
     Ok(())
 }
 
+/// Start the tick loop for a region unless one is already pending
+pub fn ensure_physics_tick_scheduled(ctx: &ReducerContext, region: u32) -> Result<(), String> {
+    if ctx.db.physics_tick_schedule().iter().any(|s| s.region == region) {
+        return Ok(());
+    }
+    schedule_physics_tick(ctx, region, None)
+}
+
+/// Height (y) at which player bodies spawn, just above the ground collider
+pub const SPAWN_HEIGHT: f32 = 1.0;
+
+/// Spawn a player's physics body at the spawn point and return its entity ID
+fn spawn_player_body(ctx: &ReducerContext, client_id: Identity) -> Result<u32, String> {
+    // Determine spawn position on the ground plane, just above the ground collider
+    let spawn_x = 50.0;
+    let spawn_z = 50.0;
+    let chunk_x = calculate_chunk(spawn_x);
+    let chunk_y = calculate_chunk(spawn_z);
+
+    // Ensure map chunks exist at spawn location before player spawns
+    crate::world::MapManager::ensure_chunks_exist_in_radius(ctx, chunk_x, chunk_y, Some(2))?;
+
+    // Player bodies can't be spawned through reducers (see `auth::spawn_role`), so go around them
+    let collider = ColliderSpec::new(ColliderShape::Sphere(0.5));
+    spawn_body(ctx, new_body_row(client_id, 0, spawn_x, SPAWN_HEIGHT, spawn_z, vec![collider], PLAYER_BODY_TYPE), None)
+}
+
 /**
  * Client connection lifecycle reducer.
  * 
@@ -138,31 +176,7 @@ pub fn on_client_connected(ctx: &ReducerContext) -> Result<(), String> {
     let existing_player = ctx.db.player().iter().find(|p| p.player_id == client_id);
     
     let _player = if existing_player.is_none() {
-        // Determine spawn position
-        let spawn_x = 50.0;
-        let spawn_y = 50.0;
-        let chunk_x = calculate_chunk(spawn_x);
-        let chunk_y = calculate_chunk(spawn_y);
-        
-        // Ensure map chunks exist at spawn location before player spawns
-        crate::world::MapManager::ensure_chunks_exist_in_radius(ctx, chunk_x, chunk_y, Some(2))?;
-        
-        // Spawn physics body for new player
-        spawn_rigid_body(
-            ctx,
-            0u32,
-            spawn_x,
-            spawn_y,
-            0.0f32,
-            "Sphere(0.5)".to_string(),
-            2u8,
-        )?;
-        // Find rigid body by owner tag and extract entity_id
-        let player_physical_object = ctx.db.physics_body()
-            .iter()
-            .find(|p| p.owner_id == client_id)
-            .ok_or_else(|| "Failed to spawn player physics body".to_string())?;
-        let player_physical_object_id = player_physical_object.entity_id;
+        let player_physical_object_id = spawn_player_body(ctx, client_id)?;
 
         // Create a new player with default stats
         let new_player = Player {
@@ -184,7 +198,13 @@ pub fn on_client_connected(ctx: &ReducerContext) -> Result<(), String> {
         // Update existing player status
         player.status = PlayerStatus::Online;
         player.last_active = ctx.timestamp;
+        // the body may have been despawned while they were away
+        if ctx.db.physics_body().entity_id().find(player.phy_entity_id).is_none() {
+            player.phy_entity_id = spawn_player_body(ctx, client_id)?;
+        }
         ctx.db.player().player_id().update(player.clone());
+        // the player's region may have gone dormant while they were away
+        wake_player_region(ctx, player.phy_entity_id);
         player
     } else {
         return Err("Failed to create or restore player".to_string());
@@ -218,6 +238,9 @@ pub fn on_client_disconnected(ctx: &ReducerContext) -> Result<(), String> {
         
         log::info!("Player {} is now offline", player.username);
     }
+
+    // Stop movement and let the client restart its input sequence on reconnect
+    ctx.db.player_input().player_id().delete(client_id);
     
     Ok(())
 }
@@ -250,6 +273,26 @@ pub fn expire_buffs(ctx: &ReducerContext, _sch: BuffExpirySchedule) -> Result<()
         ctx.db.damage_event().event_id().delete(ev.event_id);
     }
 
+    // Purge scene-query results the same way
+    let expired_hits: Vec<_> = ctx.db
+        .query_hit()
+        .iter()
+        .filter(|hit| hit.expire_at < now)
+        .collect();
+    for hit in expired_hits {
+        ctx.db.query_hit().hit_id().delete(hit.hit_id);
+    }
+
+    // ...and despawn records
+    let expired_despawns: Vec<_> = ctx.db
+        .despawn_event()
+        .iter()
+        .filter(|ev| ev.expire_at < now)
+        .collect();
+    for ev in expired_despawns {
+        ctx.db.despawn_event().event_id().delete(ev.event_id);
+    }
+
     // Schedule the next buff expiry (self-scheduling for continuous expiration)
     let next_id = _sch.scheduled_id + 1;
     let base_time = if let ScheduleAt::Time(ts) = _sch.scheduled_at { ts } else { ctx.timestamp };
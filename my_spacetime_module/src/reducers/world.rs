@@ -1,28 +1,36 @@
 use spacetimedb::{Identity, ReducerContext, Table};
-//use crate::tables::player::player;
+use crate::tables::player::player;
+use crate::tables::player_input::{player_input, PlayerInput};
 use crate::tables::game_item::game_item;
 use crate::world::MapManager;
 use crate::spacetime_common::spatial::{calculate_chunk_pair, are_chunks_adjacent_simd};
 use rapier3d::na::Point3;
-use crate::tables::physics_body::physics_body;
-use crate::physics::PHYSICS_CONTEXTS;
-use rapier3d::na::Isometry3;
+use crate::tables::physics_body::{physics_body, PhysicsBody};
+use crate::physics::{PHYSICS_CONTEXTS, world_for_region};
+use crate::physics::queries::caster_entity;
+use crate::physics::character::planar;
+use rapier3d::na::Vector3;
+
+/// The sender's own player body, via `Player.phy_entity_id` (not just any body they own)
+fn own_body(ctx: &ReducerContext) -> Option<PhysicsBody> {
+    caster_entity(ctx).and_then(|id| ctx.db.physics_body().entity_id().find(id))
+}
 
 /**
  * Player movement reducer.
  * 
- * Position-based game mechanics
+ * Position-based game mechanics. Kept for older clients: prefer `submit_input`.
+ * The target is a point on the ground plane (world x, z); it only sets a desired
+ * translation, which physics_tick caps at sprint speed.
  */
 #[spacetimedb::reducer]
-pub fn move_player(ctx: &ReducerContext, new_x: f32, new_y: f32) -> Result<(), String> {
-    let player_id = ctx.sender;
-    
-    if let Some(player_physical_object) = ctx.db.physics_body().iter().find(|p| p.owner_id == player_id) {
+pub fn move_player(ctx: &ReducerContext, new_x: f32, new_z: f32) -> Result<(), String> {
+    if let Some(player_physical_object) = own_body(ctx) {
         
         let player = player_physical_object.clone();
 
         // disallow moving further than adjacent chunks
-        let (new_chunk_x, new_chunk_y) = calculate_chunk_pair(new_x, new_y);
+        let (new_chunk_x, new_chunk_y) = calculate_chunk_pair(new_x, new_z);
         // adjacency helper
         if !are_chunks_adjacent_simd(player.chunk_x, player.chunk_y, new_chunk_x, new_chunk_y) {
             return Err("Cannot move more than one chunk at a time".to_string());
@@ -42,21 +50,21 @@ pub fn move_player(ctx: &ReducerContext, new_x: f32, new_y: f32) -> Result<(), S
             MapManager::ensure_chunks_exist_in_radius(ctx, new_chunk_x, new_chunk_y, None)?;
         }
 
-        // Nov let the simulation update physics_body position
-        log::info!("Physics_body with entity_id {} and owner_id {} will move to ({}, {}), on next physics tick", player.entity_id, player.owner_id, new_x, new_y);
-        // Teleport the player's physics body via Rapier
-        if let Some(phys) = ctx.db.physics_body().iter().find(|b| b.owner_id == player_id) {
-            let mut contexts = PHYSICS_CONTEXTS.lock().unwrap();
-            if let Some(world) = contexts.get_mut(&phys.region) {
-                // O(1) forward lookup via id_to_body map
-                if let Some(&handle) = world.id_to_body.get(&phys.entity_id) {
-                    if let Some(rb) = world.bodies.get_mut(handle) {
-                        rb.set_next_kinematic_position(Isometry3::translation(new_x, new_y, 0.0));
-                        log::info!("Teleported physics body {} to ({}, {}), on next physics tick", phys.entity_id, new_x, new_y);
-                    }
-                }
-            }
+        // Now let the simulation resolve the move against walls and slopes on the next physics tick
+        log::info!("Physics_body with entity_id {} and owner_id {} will move to ({}, {}), on next physics tick", player.entity_id, player.owner_id, new_x, new_z);
+        let mut contexts = PHYSICS_CONTEXTS.lock().unwrap();
+        let world = world_for_region(ctx, &mut contexts, player.region);
+        // O(1) forward lookup via id_to_body map
+        let handle = *world.id_to_body.get(&player.entity_id).ok_or("Player body not in physics world")?;
+        let rb = world.bodies.get(handle).ok_or("Player body not in physics world")?;
+        if !rb.is_kinematic() {
+            return Err("Player body is not kinematic".to_string());
         }
+        // desired translation in the movement plane; the controller owns the vertical axis
+        let current = rb.translation();
+        let desired = planar(Vector3::new(new_x - current.x, 0.0, new_z - current.z), &world.character_controller.up);
+        let character = world.characters.get_mut(&player.entity_id).ok_or("Player body has no character controller")?;
+        character.desired_translation = Some(desired);
         
         Ok(())
     } else {
@@ -64,6 +72,56 @@ pub fn move_player(ctx: &ReducerContext, new_x: f32, new_y: f32) -> Result<(), S
     }
 }
 
+/// True if input sequence number `seq` comes after `last`; sequence numbers wrap, so they
+/// are compared by signed distance
+pub fn seq_newer(seq: u32, last: u32) -> bool {
+    (seq.wrapping_sub(last) as i32) > 0
+}
+
+/**
+ * Movement input reducer.
+ *
+ * Stores the player's movement intent; physics_tick applies it every tick under the
+ * server's speed limits until the next input arrives. Out-of-order inputs are dropped.
+ */
+#[spacetimedb::reducer]
+pub fn submit_input(ctx: &ReducerContext, dir_x: f32, dir_y: f32, dir_z: f32, sprint: bool, jump: bool, seq: u32) -> Result<(), String> {
+    if ![dir_x, dir_y, dir_z].iter().all(|v| v.is_finite()) {
+        return Err("Invalid input direction".to_string());
+    }
+    if ctx.db.player().player_id().find(ctx.sender).is_none() {
+        return Err("Player not found".to_string());
+    }
+
+    if let Some(mut input) = ctx.db.player_input().player_id().find(ctx.sender) {
+        if !seq_newer(seq, input.seq) {
+            return Err("Stale input".to_string());
+        }
+        input.dir_x = dir_x;
+        input.dir_y = dir_y;
+        input.dir_z = dir_z;
+        input.sprint = sprint;
+        // keep an unconsumed jump until physics_tick sees it
+        input.jump |= jump;
+        input.seq = seq;
+        input.updated_at = ctx.timestamp;
+        ctx.db.player_input().player_id().update(input);
+    } else {
+        ctx.db.player_input().insert(PlayerInput {
+            player_id: ctx.sender,
+            dir_x,
+            dir_y,
+            dir_z,
+            sprint,
+            jump,
+            seq,
+            last_processed_seq: seq.wrapping_sub(1),
+            updated_at: ctx.timestamp,
+        });
+    }
+    Ok(())
+}
+
 /**
  * Item pickup reducer.
  * Proximity-based interaction
@@ -74,7 +132,7 @@ pub fn pickup_item(ctx: &ReducerContext, item_id: u64) -> Result<(), String> {
     log::info!("Player {} is trying to pick up item {}", player_id, item_id);
 
     // Verify player's object exists
-    let player_physical_object = ctx.db.physics_body().iter().find(|p| p.owner_id == player_id).ok_or("Player not found".to_string())?;
+    let player_physical_object = own_body(ctx).ok_or("Player not found".to_string())?;
 
     // Find the item
     let item = ctx.db.game_item().iter().find(|i| i.item_id == item_id).ok_or("Item not found".to_string())?.clone();
@@ -97,10 +155,10 @@ pub fn pickup_item(ctx: &ReducerContext, item_id: u64) -> Result<(), String> {
         log::warn!("Item {} has no chunk coords, skipping chunk pre-check", item_id);
     }
 
-    // Use rapier3d for precise proximity check
-    if let (Some(item_x), Some(item_y)) = (item.position_x, item.position_y) {        
-        let player_position = Point3::new(player_physical_object.pos_x, player_physical_object.pos_y, 0.0);
-        let item_position = Point3::new(item_x, item_y, 0.0);
+    // Use rapier3d for precise proximity check on the ground plane
+    if let (Some(item_x), Some(item_z)) = (item.position_x, item.position_z) {        
+        let player_position = Point3::new(player_physical_object.pos_x, 0.0, player_physical_object.pos_z);
+        let item_position = Point3::new(item_x, 0.0, item_z);
         let distance = (player_position - item_position).norm();
 
         // Define pickup radius
@@ -118,7 +176,7 @@ pub fn pickup_item(ctx: &ReducerContext, item_id: u64) -> Result<(), String> {
     updated_item.owner_id = player_id;
     updated_item.is_dropped = false;
     updated_item.position_x = None;
-    updated_item.position_y = None;
+    updated_item.position_z = None;
     updated_item.chunk_x = None;
     updated_item.chunk_y = None;
 
@@ -138,7 +196,7 @@ pub fn drop_item(ctx: &ReducerContext, item_id: u64) -> Result<(), String> {
     let player_id = ctx.sender;
     
     // Verify player exists
-    let player = match ctx.db.physics_body().iter().find(|p| p.owner_id == player_id) {
+    let player = match own_body(ctx) {
         Some(p) => p,
         None => return Err("Player not found".to_string()),
     };
@@ -154,7 +212,7 @@ pub fn drop_item(ctx: &ReducerContext, item_id: u64) -> Result<(), String> {
         item.owner_id = Identity::default();
         item.is_dropped = true;
         item.position_x = Some(player.pos_x);
-        item.position_y = Some(player.pos_y);
+        item.position_z = Some(player.pos_z);
         item.chunk_x = Some(player.chunk_x);
         item.chunk_y = Some(player.chunk_y);
         
@@ -17,4 +17,19 @@ const PHYSICS_BODY_VISIBILITY: Filter = Filter::Sql("
 #[client_visibility_filter]
 const PLAYER_TABLE_VISIBILITY: Filter = Filter::Sql("
     SELECT * FROM player WHERE player_id = :sender
-");
\ No newline at end of file
+");
+
+#[client_visibility_filter]
+const DESPAWN_EVENT_VISIBILITY: Filter = Filter::Sql("
+    SELECT * FROM despawn_event WHERE owner_id = :sender
+");
+
+#[client_visibility_filter]
+const QUERY_HIT_VISIBILITY: Filter = Filter::Sql("
+    SELECT * FROM query_hit WHERE requester = :sender
+");
+
+#[client_visibility_filter]
+const PLAYER_INPUT_VISIBILITY: Filter = Filter::Sql("
+    SELECT * FROM player_input WHERE player_id = :sender
+");
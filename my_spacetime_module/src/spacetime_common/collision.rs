@@ -9,6 +9,18 @@ pub const KINEMATIC_BODY_TYPE: u8 = 2;
 pub const PROJECTILE_BODY_TYPE: u8 = 10;
 pub const PLAYER_BODY_TYPE: u8 = 20;
 
+/// Gameplay roles packed into a collider's `object_function` (see `physics::hooks` and
+/// `contact_tracker::handle_event`)
+pub mod object_function {
+    pub const NONE:             u8 = 0;
+    pub const DAMAGE_OVER_TIME: u8 = 1;
+    pub const AURA:             u8 = 2;
+    /// Solid only from above its local +Y
+    pub const ONE_WAY_PLATFORM: u8 = 3;
+    /// Carries touching bodies along its local +X at `modifier` * 0.1 m/s
+    pub const CONVEYOR:         u8 = 4;
+}
+
 /// Bitmask groups for your game (up to 32 distinct groups)
 pub mod collision_group {
     pub const DEFAULT:    u32 = 1 << 0;
@@ -5,3 +5,4 @@ pub mod spatial;
 pub mod types;
 pub mod shape;
 pub mod collision;
+pub mod sequence;
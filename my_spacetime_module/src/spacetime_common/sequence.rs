@@ -0,0 +1,62 @@
+use spacetimedb::{ReducerContext, Table};
+use crate::tables::id_sequence::{id_sequence, IdSequence};
+use crate::tables::physics_body::physics_body;
+use crate::tables::player_buffs::player_buffs;
+use crate::tables::contact_event::contact_event;
+
+/// Persisted ID sequences that survive module reloads, one per primary-key space
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Sequence {
+    PhysicsEntity,
+    PlayerBuff,
+    ContactEvent,
+}
+
+impl Sequence {
+    fn name(self) -> &'static str {
+        match self {
+            Sequence::PhysicsEntity => "physics_entity",
+            Sequence::PlayerBuff    => "player_buff",
+            Sequence::ContactEvent  => "contact_event",
+        }
+    }
+
+    /// First free ID when the sequence row doesn't exist yet.
+    /// Scans the backing table so databases written by the old in-memory counters don't collide.
+    fn seed(self, ctx: &ReducerContext) -> u64 {
+        let max = match self {
+            Sequence::PhysicsEntity => ctx.db.physics_body().iter().map(|r| r.entity_id as u64).max(),
+            Sequence::PlayerBuff    => ctx.db.player_buffs().iter().map(|r| r.id).max(),
+            Sequence::ContactEvent  => ctx.db.contact_event().iter().map(|r| r.id).max(),
+        };
+        max.map_or(1, |m| m + 1)
+    }
+}
+
+/// Allocate the next ID from a persisted sequence
+pub fn next_id(ctx: &ReducerContext, seq: Sequence) -> u64 {
+    let name = seq.name();
+    match ctx.db.id_sequence().name().find(name.to_string()) {
+        Some(mut row) => {
+            let id = row.next_id;
+            row.next_id += 1;
+            ctx.db.id_sequence().name().update(row);
+            id
+        }
+        None => {
+            let id = seq.seed(ctx);
+            ctx.db.id_sequence().insert(IdSequence { name: name.to_string(), next_id: id + 1 });
+            id
+        }
+    }
+}
+
+/// Allocate a physics entity ID; these are packed as 32 bits into Rapier user_data
+pub fn next_entity_id(ctx: &ReducerContext) -> Result<u32, String> {
+    entity_id_from(next_id(ctx, Sequence::PhysicsEntity))
+}
+
+/// Narrow a sequence value to the 32-bit entity ID space
+pub fn entity_id_from(id: u64) -> Result<u32, String> {
+    u32::try_from(id).map_err(|_| "Physics entity ID space exhausted".to_string())
+}
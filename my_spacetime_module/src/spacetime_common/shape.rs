@@ -1,11 +1,77 @@
 use rapier3d::prelude::*;
+use rapier3d::na::DMatrix;
+use crate::tables::collider_asset::ColliderAssetData;
+use crate::tables::physics_material::{CombineRule, PhysicsMaterial};
+use spacetimedb::SpacetimeType;
+use std::fmt;
 use std::str::FromStr;
 use thiserror::Error;
 
+/// Plain 3-component vector usable in tables and reducer arguments
+#[derive(SpacetimeType, Clone, Copy, Debug, Default, PartialEq)]
+pub struct Vec3 {
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+}
+
+impl Vec3 {
+    pub const fn new(x: f32, y: f32, z: f32) -> Self {
+        Vec3 { x, y, z }
+    }
+}
+
+/// Half-height along the local Y axis plus radius, for capsules, cylinders and cones
+#[derive(SpacetimeType, Clone, Copy, Debug, PartialEq)]
+pub struct AxialSize {
+    pub half_height: f32,
+    pub radius: f32,
+}
+
 /// Supported collider shapes
+#[derive(SpacetimeType, Clone, Copy, Debug, PartialEq)]
 pub enum ColliderShape {
-    Sphere(f32),
-    Cuboid(f32, f32, f32),
+    Sphere(f32),           // radius
+    Cuboid(Vec3),          // full size along each axis
+    Capsule(AxialSize),    // Y-aligned, e.g. characters
+    Cylinder(AxialSize),
+    Cone(AxialSize),
+    ConvexHull(u64),       // collider_asset ID
+    TriMesh(u64),          // collider_asset ID
+    HeightField(u64),      // collider_asset ID
+}
+
+/// Membership / filter masks overriding the body-type defaults of `interaction_groups`
+#[derive(SpacetimeType, Clone, Copy, Debug, PartialEq)]
+pub struct CollisionGroups {
+    pub memberships: u32,
+    pub filter: u32,
+}
+
+/**
+ * Full description of a body's collider as stored in `physics_body`.
+ *
+ * `offset` / `rotation` place the collider relative to its body (rotation as a
+ * scaled axis in radians); unset `groups` / `material` fall back to the defaults
+ * for the body type and the body's own material.
+ */
+#[derive(SpacetimeType, Clone, Debug, PartialEq)]
+pub struct ColliderSpec {
+    pub shape: ColliderShape,
+    pub sensor: bool,
+    pub offset: Vec3,
+    pub rotation: Vec3,
+    pub groups: Option<CollisionGroups>,
+    /// physics_material name overriding the body's material for this collider's surface
+    pub material: Option<String>,
+    /// Emit contact-force events when the total contact force exceeds this (impact damage)
+    pub contact_force_threshold: Option<f32>,
+    /// Gameplay role packed into this collider's user data (see `collision::object_function`)
+    pub object_function: u8,
+    /// Ghost through colliders with `flag` set and the same `modifier` (allies on one team)
+    pub flag: bool,
+    /// Team for ghosting, or the object function's parameter (conveyor speed)
+    pub modifier: u8,
 }
 
 /// Errors during shape parsing
@@ -13,46 +79,225 @@ pub enum ColliderShape {
 pub enum ShapeParseError {
     #[error("invalid shape format")] InvalidFormat,
     #[error("invalid float value")] ParseFloat(#[from] std::num::ParseFloatError),
+    #[error("invalid asset id")] ParseInt(#[from] std::num::ParseIntError),
+}
+
+/// Parse exactly `N` comma-separated floats
+fn floats<const N: usize>(args: &str) -> Result<[f32; N], ShapeParseError> {
+    let parts: Vec<_> = args.split(',').map(str::trim).collect();
+    if parts.len() != N {
+        return Err(ShapeParseError::InvalidFormat);
+    }
+    let mut out = [0.0; N];
+    for (o, p) in out.iter_mut().zip(parts) {
+        *o = p.parse()?;
+    }
+    Ok(out)
 }
 
 impl FromStr for ColliderShape {
     type Err = ShapeParseError;
 
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (name, args) = s.trim()
+            .strip_suffix(')')
+            .and_then(|s| s.split_once('('))
+            .ok_or(ShapeParseError::InvalidFormat)?;
+        let axial = |args: &str| floats::<2>(args).map(|[half_height, radius]| AxialSize { half_height, radius });
+        Ok(match name.trim() {
+            "Sphere" => ColliderShape::Sphere(floats::<1>(args)?[0]),
+            "Box" => {
+                let [x, y, z] = floats::<3>(args)?;
+                ColliderShape::Cuboid(Vec3::new(x, y, z))
+            }
+            "Capsule" => ColliderShape::Capsule(axial(args)?),
+            "Cylinder" => ColliderShape::Cylinder(axial(args)?),
+            "Cone" => ColliderShape::Cone(axial(args)?),
+            "ConvexHull" => ColliderShape::ConvexHull(args.trim().parse()?),
+            "TriMesh" => ColliderShape::TriMesh(args.trim().parse()?),
+            "HeightField" => ColliderShape::HeightField(args.trim().parse()?),
+            _ => return Err(ShapeParseError::InvalidFormat),
+        })
+    }
+}
+
+impl fmt::Display for ColliderShape {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ColliderShape::Sphere(r) => write!(f, "Sphere({})", r),
+            ColliderShape::Cuboid(s) => write!(f, "Box({}, {}, {})", s.x, s.y, s.z),
+            ColliderShape::Capsule(a) => write!(f, "Capsule({}, {})", a.half_height, a.radius),
+            ColliderShape::Cylinder(a) => write!(f, "Cylinder({}, {})", a.half_height, a.radius),
+            ColliderShape::Cone(a) => write!(f, "Cone({}, {})", a.half_height, a.radius),
+            ColliderShape::ConvexHull(id) => write!(f, "ConvexHull({})", id),
+            ColliderShape::TriMesh(id) => write!(f, "TriMesh({})", id),
+            ColliderShape::HeightField(id) => write!(f, "HeightField({})", id),
+        }
+    }
+}
+
+/// Compatibility grammar: a shape optionally followed by `Sensor`, e.g. `Sphere(3.0)Sensor`
+impl FromStr for ColliderSpec {
+    type Err = ShapeParseError;
+
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let s = s.trim();
-        if let Some(inner) = s.strip_prefix("Sphere(") {
-            if let Some(val) = inner.strip_suffix(")") {
-                let r = val.parse::<f32>()?;
-                return Ok(ColliderShape::Sphere(r));
+        let (shape, sensor) = match s.len().checked_sub("sensor".len()) {
+            Some(at) if s.is_char_boundary(at) && s[at..].eq_ignore_ascii_case("sensor") => (&s[..at], true),
+            _ => (s, false),
+        };
+        let mut spec = ColliderSpec::new(shape.parse()?);
+        spec.sensor = sensor;
+        Ok(spec)
+    }
+}
+
+/// Only shape and sensor flag survive the string form
+impl fmt::Display for ColliderSpec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}", self.shape, if self.sensor { "Sensor" } else { "" })
+    }
+}
+
+impl ColliderShape {
+    /// The collider_asset this shape is built from, if any
+    pub fn asset_id(&self) -> Option<u64> {
+        match *self {
+            ColliderShape::ConvexHull(id) | ColliderShape::TriMesh(id) | ColliderShape::HeightField(id) => Some(id),
+            _ => None,
+        }
+    }
+
+    /// Build a Rapier ColliderBuilder from this shape; `asset` is the row named by `asset_id`
+    pub fn to_rapier(&self, asset: Option<&ColliderAssetData>, is_sensor: bool, groups: InteractionGroups) -> Result<ColliderBuilder, String> {
+        let point = |v: &Vec3| point![v.x, v.y, v.z];
+        let builder = match (*self, asset) {
+            (ColliderShape::Sphere(r), _) => ColliderBuilder::ball(r),
+            (ColliderShape::Cuboid(s), _) => ColliderBuilder::cuboid(s.x / 2.0, s.y / 2.0, s.z / 2.0),
+            (ColliderShape::Capsule(a), _) => ColliderBuilder::capsule_y(a.half_height, a.radius),
+            (ColliderShape::Cylinder(a), _) => ColliderBuilder::cylinder(a.half_height, a.radius),
+            (ColliderShape::Cone(a), _) => ColliderBuilder::cone(a.half_height, a.radius),
+            (ColliderShape::ConvexHull(_), Some(ColliderAssetData::ConvexHull(points))) => {
+                let points: Vec<_> = points.iter().map(point).collect();
+                ColliderBuilder::convex_hull(&points).ok_or("Degenerate convex hull")?
+            }
+            (ColliderShape::TriMesh(_), Some(ColliderAssetData::TriMesh(mesh))) => {
+                let vertices = mesh.vertices.iter().map(point).collect();
+                let indices = mesh.indices.chunks_exact(3).map(|t| [t[0], t[1], t[2]]).collect();
+                ColliderBuilder::trimesh(vertices, indices).map_err(|e| format!("Invalid triangle mesh: {:?}", e))?
             }
+            (ColliderShape::HeightField(_), Some(ColliderAssetData::HeightField(h))) => {
+                let heights = DMatrix::from_row_slice(h.nrows as usize, h.ncols as usize, &h.heights);
+                ColliderBuilder::heightfield(heights, vector![h.scale.x, h.scale.y, h.scale.z])
+            }
+            (shape, Some(_)) => return Err(format!("Collider asset {} doesn't match shape {}", shape.asset_id().unwrap_or_default(), shape)),
+            (shape, None) => return Err(format!("Collider asset {} not found", shape.asset_id().unwrap_or_default())),
+        };
+        Ok(builder
+            .sensor(is_sensor)
+            .active_events(ActiveEvents::COLLISION_EVENTS)
+            // filtering and contact modification are decided by `physics::hooks::GameHooks`
+            .active_hooks(ActiveHooks::FILTER_CONTACT_PAIRS | ActiveHooks::FILTER_INTERSECTION_PAIR | ActiveHooks::MODIFY_SOLVER_CONTACTS)
+            .collision_groups(groups))
+    }
+
+    /// Radius of a sphere around the shape's origin enclosing it; None for asset-backed shapes
+    pub fn bounding_radius(&self) -> Option<f32> {
+        match *self {
+            ColliderShape::Sphere(r) => Some(r),
+            ColliderShape::Cuboid(s) => Some(0.5 * (s.x * s.x + s.y * s.y + s.z * s.z).sqrt()),
+            ColliderShape::Capsule(a) => Some(a.half_height + a.radius),
+            ColliderShape::Cylinder(a) | ColliderShape::Cone(a) => Some(a.half_height.hypot(a.radius)),
+            ColliderShape::ConvexHull(_) | ColliderShape::TriMesh(_) | ColliderShape::HeightField(_) => None,
         }
-        if let Some(inner) = s.strip_prefix("Box(") {
-            if let Some(val) = inner.strip_suffix(")") {
-                let parts: Vec<_> = val.split(',').map(str::trim).collect();
-                if parts.len() == 3 {
-                    let x = parts[0].parse()?;
-                    let y = parts[1].parse()?;
-                    let z = parts[2].parse()?;
-                    return Ok(ColliderShape::Cuboid(x, y, z));
-                }
+    }
+
+    fn dimensions_valid(&self) -> bool {
+        let positive = |v: f32| v.is_finite() && v > 0.0;
+        match *self {
+            ColliderShape::Sphere(r) => positive(r),
+            ColliderShape::Cuboid(s) => positive(s.x) && positive(s.y) && positive(s.z),
+            ColliderShape::Capsule(a) | ColliderShape::Cylinder(a) | ColliderShape::Cone(a) => {
+                positive(a.half_height) && positive(a.radius)
             }
+            // asset geometry is validated when the asset is created
+            ColliderShape::ConvexHull(_) | ColliderShape::TriMesh(_) | ColliderShape::HeightField(_) => true,
         }
-        Err(ShapeParseError::InvalidFormat)
     }
 }
 
-impl ColliderShape {
-    /// Build a Rapier ColliderBuilder from this shape
-    pub fn to_rapier(&self, is_sensor: bool, groups: InteractionGroups) -> ColliderBuilder {
-        match *self {
-            ColliderShape::Sphere(r) => ColliderBuilder::ball(r)
-                .sensor(is_sensor)
-                .active_events(ActiveEvents::COLLISION_EVENTS)
-                .collision_groups(groups),
-            ColliderShape::Cuboid(x, y, z) => ColliderBuilder::cuboid(x / 2.0, y / 2.0, z / 2.0)
-                .sensor(is_sensor)
-                .active_events(ActiveEvents::COLLISION_EVENTS)
-                .collision_groups(groups),
+impl ColliderSpec {
+    /// A solid collider centred on its body with default groups and material
+    pub fn new(shape: ColliderShape) -> Self {
+        ColliderSpec {
+            shape,
+            sensor: false,
+            offset: Vec3::default(),
+            rotation: Vec3::default(),
+            groups: None,
+            material: None,
+            contact_force_threshold: None,
+            object_function: 0,
+            flag: false,
+            modifier: 0,
+        }
+    }
+
+    /// Reject specs Rapier can't build sensibly (non-positive sizes, NaNs)
+    pub fn validate(&self) -> Result<(), String> {
+        if !self.shape.dimensions_valid() {
+            return Err("Collider dimensions must be positive".into());
+        }
+        let finite = [self.offset, self.rotation].iter().all(|v| v.x.is_finite() && v.y.is_finite() && v.z.is_finite());
+        if !finite {
+            return Err("Non-finite collider offset".into());
         }
+        if self.contact_force_threshold.is_some_and(|t| !t.is_finite() || t < 0.0) {
+            return Err("Contact force threshold must be non-negative".into());
+        }
+        Ok(())
     }
-}
\ No newline at end of file
+
+    /// Build a Rapier ColliderBuilder; `default_groups` applies when no groups are set,
+    /// `surface` is the resolved material (this spec's, else the body's) and
+    /// `impact_threshold` enables contact-force events (resolved by the caller, since a
+    /// spec's own threshold only counts for trusted owners)
+    pub fn to_rapier(
+        &self,
+        asset: Option<&ColliderAssetData>,
+        default_groups: InteractionGroups,
+        surface: Option<&PhysicsMaterial>,
+        impact_threshold: Option<f32>,
+    ) -> Result<ColliderBuilder, String> {
+        let groups = self.groups.map_or(default_groups, |g| {
+            InteractionGroups::new(Group::from_bits_truncate(g.memberships), Group::from_bits_truncate(g.filter))
+        });
+        let mut builder = self.shape.to_rapier(asset, self.sensor, groups)?
+            .translation(vector![self.offset.x, self.offset.y, self.offset.z])
+            .rotation(vector![self.rotation.x, self.rotation.y, self.rotation.z]);
+        if let Some(threshold) = impact_threshold {
+            builder = builder
+                .active_events(ActiveEvents::COLLISION_EVENTS | ActiveEvents::CONTACT_FORCE_EVENTS)
+                .contact_force_event_threshold(threshold);
+        }
+        if let Some(m) = surface {
+            builder = builder
+                .friction(m.friction)
+                .restitution(m.restitution)
+                .density(m.density)
+                .friction_combine_rule(combine_rule(m.friction_combine))
+                .restitution_combine_rule(combine_rule(m.restitution_combine));
+        }
+        Ok(builder)
+    }
+}
+
+/// Map the table's combine rule onto Rapier's
+pub fn combine_rule(rule: CombineRule) -> CoefficientCombineRule {
+    match rule {
+        CombineRule::Average => CoefficientCombineRule::Average,
+        CombineRule::Min => CoefficientCombineRule::Min,
+        CombineRule::Multiply => CoefficientCombineRule::Multiply,
+        CombineRule::Max => CoefficientCombineRule::Max,
+    }
+}
@@ -1,4 +1,7 @@
 use wide::{f32x4, i32x4};
+// The chunk grid covers the ground plane: world x and z (y is up, against gravity).
+// A chunk pair's second coordinate (`chunk_y` columns) is therefore the world z axis.
+
 /// Size of one chunk in world units
 pub const CHUNK_SIZE: f32 = 10.0;
 
@@ -0,0 +1,76 @@
+use spacetimedb::{SpacetimeType, Identity, Timestamp};
+use crate::spacetime_common::shape::Vec3;
+
+/// Indexed triangle mesh; `indices` holds three vertex indices per triangle
+#[derive(SpacetimeType, Clone, Debug, PartialEq)]
+pub struct MeshData {
+    pub vertices: Vec<Vec3>,
+    pub indices: Vec<u32>,
+}
+
+/// Grid of heights, row-major with `nrows` samples along z and `ncols` along x,
+/// stretched over `scale` (x/z = total size, y = height multiplier)
+#[derive(SpacetimeType, Clone, Debug, PartialEq)]
+pub struct HeightFieldData {
+    pub nrows: u32,
+    pub ncols: u32,
+    pub heights: Vec<f32>,
+    pub scale: Vec3,
+}
+
+/// Geometry payload of a collider asset
+#[derive(SpacetimeType, Clone, Debug, PartialEq)]
+pub enum ColliderAssetData {
+    ConvexHull(Vec<Vec3>),  // point cloud; the hull is computed at build time
+    TriMesh(MeshData),
+    HeightField(HeightFieldData),
+}
+
+/**
+ * Mesh-type collider geometry, stored once and referenced by ID from
+ * `ColliderShape::{ConvexHull, TriMesh, HeightField}` so it isn't copied onto
+ * every `physics_body` row. Public so clients can render the same geometry.
+ */
+#[derive(Clone, Debug)]
+#[spacetimedb::table(name = collider_asset, public)]
+pub struct ColliderAsset {
+    #[primary_key]
+    #[auto_inc]
+    pub asset_id: u64,
+    #[unique]
+    pub name: String,
+    pub data: ColliderAssetData,
+    pub created_by: Identity,
+    pub created_at: Timestamp,
+}
+
+impl ColliderAssetData {
+    /// Check the geometry is well-formed before it's stored
+    pub fn validate(&self) -> Result<(), String> {
+        let finite = |v: &Vec3| v.x.is_finite() && v.y.is_finite() && v.z.is_finite();
+        match self {
+            ColliderAssetData::ConvexHull(points) => {
+                if points.len() < 4 || !points.iter().all(finite) {
+                    return Err("Convex hull needs at least 4 finite points".into());
+                }
+            }
+            ColliderAssetData::TriMesh(mesh) => {
+                if mesh.indices.is_empty() || mesh.indices.len() % 3 != 0 || !mesh.vertices.iter().all(finite) {
+                    return Err("Triangle mesh needs finite vertices and 3 indices per triangle".into());
+                }
+                if mesh.indices.iter().any(|&i| i as usize >= mesh.vertices.len()) {
+                    return Err("Triangle mesh index out of range".into());
+                }
+            }
+            ColliderAssetData::HeightField(h) => {
+                if h.nrows < 2 || h.ncols < 2 || h.heights.len() != (h.nrows as usize) * (h.ncols as usize) {
+                    return Err("Heightfield needs at least 2x2 samples and nrows * ncols heights".into());
+                }
+                if !h.heights.iter().all(|v| v.is_finite()) || !finite(&h.scale) || h.scale.x <= 0.0 || h.scale.z <= 0.0 {
+                    return Err("Heightfield heights and scale must be finite with positive x/z scale".into());
+                }
+            }
+        }
+        Ok(())
+    }
+}
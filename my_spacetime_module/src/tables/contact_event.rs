@@ -5,7 +5,9 @@ use spacetimedb::{table, Timestamp};
 pub struct ContactEvent {
     #[primary_key]
     pub id: u64,
+    #[index(btree)]
     pub entity_1: u32,
+    #[index(btree)]
     pub entity_2: u32,
     pub started_at: Timestamp,
 }
@@ -0,0 +1,32 @@
+use spacetimedb::{Identity, SpacetimeType, Timestamp};
+
+/// Why an entity was removed from the physics world
+#[derive(SpacetimeType, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DespawnReason {
+    /// `despawn_rigid_body` was called
+    Requested,
+    /// A projectile hit something with `ImpactBehavior::Despawn` or used up `max_hits`
+    ProjectileSpent,
+    /// A projectile outlived `max_lifetime_ms` or flew past `max_distance`
+    ProjectileExpired,
+    /// A damage-over-time source reached its hit limit
+    HitLimit,
+}
+
+/// One row per despawned entity so clients can tell a kill from a cleanup (explosion vs fade);
+/// purged after `DESPAWN_EVENT_TTL_MICROS` like `damage_event`
+#[derive(Clone, Debug)]
+#[spacetimedb::table(name = despawn_event, public)]
+pub struct DespawnEvent {
+    #[primary_key]
+    #[auto_inc]
+    pub event_id: u64,
+    #[index(btree)]
+    pub entity_id: u32,
+    pub owner_id: Identity,
+    pub region: u32,
+    pub body_type: u8,
+    pub reason: DespawnReason,
+    pub despawned_at: Timestamp,
+    pub expire_at: Timestamp,
+}
@@ -18,8 +18,9 @@ pub struct GameItem {
     pub name: String,
     pub item_type: String,
     pub value: u32,
+    // Position on the ground plane (world x, z)
     pub position_x: Option<f32>,
-    pub position_y: Option<f32>,
+    pub position_z: Option<f32>,
     // Chunk coordinates for spatial partitioning
     pub chunk_x: Option<i32>, 
     pub chunk_y: Option<i32>, 
@@ -0,0 +1,11 @@
+use spacetimedb::table;
+
+/// Persisted ID counters, one row per sequence name (see `spacetime_common::sequence`)
+#[table(name = id_sequence)]
+#[derive(Clone)]
+pub struct IdSequence {
+    #[primary_key]
+    pub name: String,
+    /// Next ID to hand out
+    pub next_id: u64,
+}
@@ -1,4 +1,5 @@
 use spacetimedb::Identity;
+use crate::spacetime_common::shape::ColliderSpec;
 
 #[spacetimedb::table(
     name = physics_body, public,
@@ -33,7 +34,11 @@ pub struct PhysicsBody {
     pub ang_vel_x: f32,
     pub ang_vel_y: f32,
     pub ang_vel_z: f32,
-    // Collider descriptor and body type
-    pub collider_shape: String,
+    // Collider descriptors (first is the primary collider) and body type
+    pub colliders: Vec<ColliderSpec>,
+    /// physics_material name for damping, gravity scale, locks, CCD and default collider surface
+    pub material: Option<String>,
     pub body_type: u8,
+    /// Character bodies only: standing on ground after the last controller move
+    pub grounded: bool,
 }
\ No newline at end of file
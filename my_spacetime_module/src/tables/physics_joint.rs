@@ -0,0 +1,100 @@
+use spacetimedb::{SpacetimeType, Identity};
+
+/// Kind of Rapier impulse joint connecting two physics bodies
+#[derive(SpacetimeType, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum JointKind {
+    Fixed,     // welds both bodies together
+    Revolute,  // hinge around `axis` (doors, wheels)
+    Prismatic, // slides along `axis` (pistons, elevators)
+    Spherical, // ball-and-socket (chains, ragdolls)
+    Rope,      // max distance between anchors (bridges, tethers)
+}
+
+/// Limits along the joint's free axis: radians for revolute, distance for prismatic.
+/// Rope joints use `max` as their length.
+#[derive(SpacetimeType, Clone, Copy, PartialEq, Debug)]
+pub struct JointLimitSpec {
+    pub min: f32,
+    pub max: f32,
+}
+
+/// Motor driving the joint's free axis towards a target position and/or velocity
+#[derive(SpacetimeType, Clone, Copy, PartialEq, Debug)]
+pub struct JointMotorSpec {
+    pub target_pos: f32,
+    pub target_vel: f32,
+    pub stiffness: f32,
+    pub damping: f32,
+    pub max_force: f32,
+}
+
+/**
+ * Joint between two physics bodies of the same region.
+ *
+ * Rows are the source of truth: joints are rebuilt from this table when a region's
+ * world is hydrated, and clients subscribe to it to render constraints.
+ */
+#[spacetimedb::table(name = physics_joint, public)]
+#[derive(Clone, Debug)]
+pub struct PhysicsJoint {
+    #[primary_key]
+    #[auto_inc]
+    pub joint_id: u64,
+    #[index(btree)]
+    pub region: u32,
+    #[index(btree)]
+    pub body_a: u32,
+    #[index(btree)]
+    pub body_b: u32,
+    pub owner_id: Identity,
+    pub kind: JointKind,
+    // Anchor points in each body's local frame
+    pub anchor_a_x: f32,
+    pub anchor_a_y: f32,
+    pub anchor_a_z: f32,
+    pub anchor_b_x: f32,
+    pub anchor_b_y: f32,
+    pub anchor_b_z: f32,
+    // Free axis for revolute & prismatic joints (ignored by the others)
+    pub axis_x: f32,
+    pub axis_y: f32,
+    pub axis_z: f32,
+    pub limits: Option<JointLimitSpec>,
+    pub motor: Option<JointMotorSpec>,
+    /// Whether the two jointed bodies still collide with each other
+    pub contacts_enabled: bool,
+}
+
+impl PhysicsJoint {
+    /// Reject values Rapier can't use sensibly (NaNs, negative lengths or forces)
+    pub fn validate(&self) -> Result<(), String> {
+        let vectors = [
+            self.anchor_a_x, self.anchor_a_y, self.anchor_a_z,
+            self.anchor_b_x, self.anchor_b_y, self.anchor_b_z,
+            self.axis_x, self.axis_y, self.axis_z,
+        ];
+        if !vectors.iter().all(|v| v.is_finite()) {
+            return Err("Joint anchors and axis must be finite".into());
+        }
+        if let Some(limits) = self.limits {
+            if !limits.min.is_finite() || !limits.max.is_finite() {
+                return Err("Joint limits must be finite".into());
+            }
+            if limits.min > limits.max {
+                return Err("Joint limits min must not exceed max".into());
+            }
+            if self.kind == JointKind::Rope && limits.max < 0.0 {
+                return Err("Rope length must be non-negative".into());
+            }
+        }
+        if let Some(m) = self.motor {
+            if ![m.target_pos, m.target_vel, m.stiffness, m.damping, m.max_force].iter().all(|v| v.is_finite()) {
+                return Err("Joint motor values must be finite".into());
+            }
+            if m.max_force < 0.0 {
+                return Err("Joint motor max_force must be non-negative".into());
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,111 @@
+use spacetimedb::{table, SpacetimeType};
+
+/// How two colliders' coefficients are combined (mirrors Rapier's `CoefficientCombineRule`)
+#[derive(SpacetimeType, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CombineRule {
+    Average,
+    Min,
+    Multiply,
+    Max,
+}
+
+/// Rotation axes a body is not allowed to turn around
+#[derive(SpacetimeType, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct LockedRotations {
+    pub x: bool,
+    pub y: bool,
+    pub z: bool,
+}
+
+/// Named surface and body properties, referenced by `physics_body.material` (whole body)
+/// and `ColliderSpec::material` (surface of one collider). Changes apply to bodies
+/// spawned or hydrated afterwards.
+#[table(name = physics_material, public)]
+#[derive(Clone, Debug, PartialEq)]
+pub struct PhysicsMaterial {
+    #[primary_key]
+    pub name: String,
+    // collider surface
+    pub friction: f32,
+    pub restitution: f32,
+    pub density: f32,
+    pub friction_combine: CombineRule,
+    pub restitution_combine: CombineRule,
+    // rigid body
+    pub linear_damping: f32,
+    pub angular_damping: f32,
+    pub gravity_scale: f32,
+    pub locked_rotations: LockedRotations,
+    pub ccd: bool,
+    /// Contact-force threshold (N) for impact damage on colliders with this surface;
+    /// used when the collider's own spec sets none or its owner may not set one
+    pub impact_threshold: Option<f32>,
+}
+
+impl PhysicsMaterial {
+    /// Materials seeded at module init
+    pub fn presets() -> Vec<Self> {
+        let base = |name: &str| PhysicsMaterial {
+            name: name.to_string(),
+            friction: 0.5,
+            restitution: 0.0,
+            density: 1.0,
+            friction_combine: CombineRule::Average,
+            restitution_combine: CombineRule::Average,
+            linear_damping: 0.0,
+            angular_damping: 0.0,
+            gravity_scale: 1.0,
+            locked_rotations: LockedRotations::default(),
+            ccd: false,
+            impact_threshold: None,
+        };
+        vec![
+            PhysicsMaterial {
+                friction: 0.02,
+                restitution: 0.05,
+                density: 0.92,
+                friction_combine: CombineRule::Min,
+                angular_damping: 0.1,
+                ..base("ice")
+            },
+            PhysicsMaterial {
+                friction: 1.0,
+                restitution: 0.85,
+                density: 1.1,
+                friction_combine: CombineRule::Max,
+                restitution_combine: CombineRule::Max,
+                linear_damping: 0.1,
+                angular_damping: 0.3,
+                ..base("rubber")
+            },
+            PhysicsMaterial {
+                friction: 0.4,
+                restitution: 0.2,
+                density: 7.8,
+                angular_damping: 0.05,
+                ..base("metal")
+            },
+        ]
+    }
+
+    /// Reject values Rapier can't use sensibly
+    pub fn validate(&self) -> Result<(), String> {
+        if self.name.trim().is_empty() {
+            return Err("Material name must not be empty".into());
+        }
+        let non_negative = [self.friction, self.restitution, self.linear_damping, self.angular_damping];
+        if !non_negative.iter().all(|v| v.is_finite() && *v >= 0.0) {
+            return Err("Friction, restitution and damping must be non-negative".into());
+        }
+        if !self.density.is_finite() || self.density <= 0.0 {
+            return Err("Density must be positive".into());
+        }
+        if !self.gravity_scale.is_finite() {
+            return Err("Gravity scale must be finite".into());
+        }
+        if self.impact_threshold.is_some_and(|t| !t.is_finite() || t < 0.0) {
+            return Err("Impact threshold must be non-negative".into());
+        }
+        Ok(())
+    }
+}
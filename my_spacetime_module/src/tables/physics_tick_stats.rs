@@ -0,0 +1,23 @@
+use spacetimedb::Timestamp;
+
+/// Per-region counters written by every physics tick, for monitoring load and back-pressure
+#[derive(Clone, Debug)]
+#[spacetimedb::table(name = physics_tick_stats, public)]
+pub struct PhysicsTickStats {
+    #[primary_key]
+    pub region: u32,
+    pub ticks: u64,
+    /// Collision events handled by the last tick
+    pub events_processed: u32,
+    /// Events left waiting for the next tick when the last tick hit its budget
+    pub events_deferred: u32,
+    /// Running total of events that had to wait at least one tick
+    pub total_deferred: u64,
+    /// physics_body rows the last tick wrote
+    pub rows_written: u32,
+    /// Non-fixed bodies the last tick didn't write (asleep or changed less than the epsilons)
+    pub rows_skipped: u32,
+    pub updated_at: Timestamp,
+    /// Set while the region is dormant (no players, no ticks; see `physics::dormancy`)
+    pub dormant_since: Option<Timestamp>,
+}
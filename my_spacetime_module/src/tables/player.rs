@@ -15,6 +15,9 @@ pub enum PlayerStatus {
     Playing,  // Player is actively engaged in gameplay
 }
 
+/// `Player::phy_entity_id` of a player whose body was despawned (entity IDs start at 1)
+pub const NO_PHYSICS_ENTITY: u32 = 0;
+
 /**
  * Player entity representing a user in the game world.
  * 
@@ -34,5 +37,6 @@ pub struct Player {
     pub score: u32,
     pub status: PlayerStatus, // Current connection/gameplay state
     pub last_active: Timestamp, // Last activity timestamp for timeout logic
+    #[index(btree)]
     pub phy_entity_id: u32, // ID of the associated physics body (primary key)
 }
\ No newline at end of file
@@ -0,0 +1,27 @@
+use spacetimedb::{Identity, Timestamp};
+
+/**
+ * Latest movement intent per player, consumed by physics_tick.
+ *
+ * Clients send a direction instead of absolute coordinates; the server turns it into
+ * movement under its own speed limits. `last_processed_seq` tells the client which of its
+ * inputs the authoritative state already includes, for reconciling predicted movement.
+ */
+#[spacetimedb::table(name = player_input, public)]
+#[derive(Clone, Debug)]
+pub struct PlayerInput {
+    #[primary_key]
+    pub player_id: Identity,
+    // Desired movement direction; clamped to unit length by the server
+    pub dir_x: f32,
+    pub dir_y: f32,
+    pub dir_z: f32,
+    pub sprint: bool,
+    /// Jump request, cleared once consumed
+    pub jump: bool,
+    /// Client sequence number of this input
+    pub seq: u32,
+    /// Sequence number of the last input applied by physics_tick
+    pub last_processed_seq: u32,
+    pub updated_at: Timestamp,
+}
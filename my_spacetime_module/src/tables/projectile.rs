@@ -0,0 +1,37 @@
+use spacetimedb::{SpacetimeType, Identity, Timestamp};
+
+/// What a projectile does when it hits something
+#[derive(SpacetimeType, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ImpactBehavior {
+    Despawn, // removed on the first hit
+    Pierce,  // sensor collider, passes through targets until max_hits
+    Bounce,  // fully elastic collider, bounces until max_hits
+}
+
+/// Lifecycle limits chosen at spawn time
+#[derive(SpacetimeType, Clone, Copy, PartialEq, Debug)]
+pub struct ProjectileSpec {
+    pub max_lifetime_ms: u32,
+    pub max_hits: u8,
+    pub max_distance: f32,
+    pub on_impact: ImpactBehavior,
+}
+
+/**
+ * Lifecycle state of a projectile physics body (same entity_id as its physics_body row).
+ * physics_tick despawns the projectile once any of its limits is reached.
+ */
+#[spacetimedb::table(name = projectile, public)]
+#[derive(Clone, Debug)]
+pub struct Projectile {
+    #[primary_key]
+    pub entity_id: u32,
+    pub owner_id: Identity,
+    pub spawned_at: Timestamp,
+    // Spawn position, for the max_distance check
+    pub origin_x: f32,
+    pub origin_y: f32,
+    pub origin_z: f32,
+    pub spec: ProjectileSpec,
+    pub hits: u8,
+}
@@ -0,0 +1,57 @@
+use spacetimedb::{SpacetimeType, Identity, Timestamp};
+
+/// Shape swept or tested by the shape-cast and overlap reducers
+#[derive(SpacetimeType, Clone, Copy, Debug, PartialEq)]
+pub enum QueryShape {
+    Sphere(f32),           // radius
+    Cuboid(HalfExtents),   // box by half extents
+}
+
+#[derive(SpacetimeType, Clone, Copy, Debug, PartialEq)]
+pub struct HalfExtents {
+    pub hx: f32,
+    pub hy: f32,
+    pub hz: f32,
+}
+
+/// Client-side filter for scene queries; mirrors Rapier's `InteractionGroups`
+/// (`u32::MAX` for both masks matches everything)
+#[derive(SpacetimeType, Clone, Copy, Debug, PartialEq)]
+pub struct QueryFilterSpec {
+    pub memberships: u32,
+    pub filter: u32,
+    /// Skip the caller's own player body
+    pub exclude_caster: bool,
+    pub exclude_sensors: bool,
+}
+
+/**
+ * One result of a scene-query reducer (raycast, shape-cast, overlap).
+ *
+ * Reducers can't return data, so each hit is written here tagged with the
+ * requester and their `query_id`; rows expire like `damage_event`.
+ */
+#[derive(Clone, Debug)]
+#[spacetimedb::table(name = query_hit, public)]
+pub struct QueryHit {
+    #[primary_key]
+    #[auto_inc]
+    pub hit_id: u64,
+
+    #[index(btree)]
+    pub requester: Identity,
+    /// Chosen by the client to match hits to the request that produced them
+    pub query_id: u32,
+    pub region: u32,
+
+    pub entity_id: u32,
+    pub point_x: f32,
+    pub point_y: f32,
+    pub point_z: f32,
+    pub normal_x: f32,
+    pub normal_y: f32,
+    pub normal_z: f32,
+    /// Distance along the ray / sweep (0 for overlaps)
+    pub toi: f32,
+    pub expire_at: Timestamp,
+}
@@ -0,0 +1,68 @@
+use spacetimedb::table;
+use crate::physics::physics_tick::MAX_COLLISION_EVENTS;
+use crate::physics::rewind::MAX_REWIND_MICROS;
+use crate::reducers::lifecycle::PHYSICS_TICK_INTERVAL_MICROS;
+
+/// Per-region physics tuning, read by physics_tick whenever it creates or steps a PhysicsContext.
+/// Regions without a row use `RegionConfig::defaults`.
+#[table(name = region_config, public)]
+#[derive(Clone, Debug, PartialEq)]
+pub struct RegionConfig {
+    #[primary_key]
+    pub region: u32,
+    pub gravity_x: f32,
+    pub gravity_y: f32,
+    pub gravity_z: f32,
+    /// Delay between scheduled physics ticks
+    pub tick_interval_micros: i64,
+    /// Rapier `num_solver_iterations` (must be at least 1)
+    pub solver_iterations: u32,
+    /// Rapier `max_ccd_substeps`; 0 disables CCD for the whole region
+    pub max_ccd_substeps: u32,
+    /// Rapier `min_ccd_dt`
+    pub min_ccd_dt: f32,
+    /// Collision events processed per tick; the rest are deferred to the next tick
+    pub max_collision_events: u32,
+    /// How far back (µs) hit queries may be rewound to match what a lagging client saw
+    pub max_rewind_micros: i64,
+    /// Inclusive chunk range owned by this region; bodies leaving it are handed off to the
+    /// region whose range contains their new chunk. Empty (min > max) owns nothing
+    pub min_chunk_x: i32,
+    pub max_chunk_x: i32,
+    pub min_chunk_y: i32,
+    pub max_chunk_y: i32,
+}
+
+impl RegionConfig {
+    /// Built-in configuration used until an admin writes a row for the region; it owns no
+    /// chunks, so a row needs an explicit range (see `set_region_config`)
+    pub fn defaults(region: u32) -> Self {
+        RegionConfig {
+            region,
+            gravity_x: 0.0,
+            gravity_y: -9.81,
+            gravity_z: 0.0,
+            tick_interval_micros: PHYSICS_TICK_INTERVAL_MICROS,
+            solver_iterations: 4,
+            max_ccd_substeps: 1,
+            min_ccd_dt: 1.0 / 60.0 / 100.0,
+            max_collision_events: MAX_COLLISION_EVENTS as u32,
+            max_rewind_micros: MAX_REWIND_MICROS,
+            min_chunk_x: 0,
+            max_chunk_x: -1,
+            min_chunk_y: 0,
+            max_chunk_y: -1,
+        }
+    }
+
+    /// True if the region owns at least one chunk
+    pub fn has_chunks(&self) -> bool {
+        self.min_chunk_x <= self.max_chunk_x && self.min_chunk_y <= self.max_chunk_y
+    }
+
+    /// True if the chunk lies inside this region's chunk range
+    pub fn contains_chunk(&self, chunk_x: i32, chunk_y: i32) -> bool {
+        (self.min_chunk_x..=self.max_chunk_x).contains(&chunk_x)
+            && (self.min_chunk_y..=self.max_chunk_y).contains(&chunk_y)
+    }
+}
@@ -5,6 +5,7 @@ use crate::physics::physics_tick::physics_tick;
 #[derive(Clone)]
 pub struct PhysicsTickSchedule {
     #[primary_key]
+    #[auto_inc]
     pub scheduled_id: u64,
     pub scheduled_at: ScheduleAt,
     pub region: u32,
@@ -0,0 +1,23 @@
+use spacetimedb::{table, Identity, SpacetimeType, Timestamp};
+
+/// Permission level of an identity; each role includes everything the roles before it may do
+#[derive(SpacetimeType, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Role {
+    /// Every connected client without a row: moves its own body, fires its own projectiles
+    Player,
+    /// May place dynamic and kinematic props and remove the ones it owns
+    Builder,
+    /// Static level geometry, region config, materials, assets, roles; may act on any body
+    Admin,
+}
+
+/// Identities with a role above `Role::Player`
+#[table(name = user_role)]
+#[derive(Clone)]
+pub struct UserRole {
+    #[primary_key]
+    pub identity: Identity,
+    pub role: Role,
+    pub granted_by: Identity,
+    pub granted_at: Timestamp,
+}